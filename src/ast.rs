@@ -1,7 +1,16 @@
-use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::collections::{BTreeMap, HashMap};
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::path::PathBuf;
 use crate::builtin_functions;
+use crate::capabilities::{Capabilities, Capability};
+use crate::error::{EarlyReturn, ErrorKind, RuntimeError, Thrown};
+use crate::interrupt;
+use crate::limits::{Budget, Limits};
+use crate::module;
+use crate::parser::Parser;
+use crate::random::Random;
 
 type BuiltInFnClosure = Rc<
     dyn Fn(
@@ -9,10 +18,34 @@ type BuiltInFnClosure = Rc<
     ) -> Result<Expression, Box<dyn std::error::Error>>
 >;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone)]
 pub enum Expression {
     List(Vec<Expression>),
-    IntegerValue(u64),
+    //.. MapLiteral: key and value expressions, evaluated into a `Map`
+    MapLiteral(Vec<(Expression, Expression)>),
+    //.. Map: values by key, iterated in the order of the keys
+    Map(BTreeMap<MapKey, Expression>),
+    //.. Record: field names and values, in declaration order
+    Record(Vec<(String, Expression)>),
+    //.. RecordUpdate: original record, replaced fields
+    RecordUpdate(Box<Expression>, Vec<(String, Expression)>),
+    //.. FieldAccess: record, field name
+    FieldAccess(Box<Expression>, String),
+    //.. TypeDeclaration: type name, constructor names and their field names
+    TypeDeclaration(String, Vec<(String, Vec<String>)>),
+    //.. Variant: constructor name, field values
+    Variant(String, Vec<Expression>),
+    //.. Match: matched value, patterns and the block evaluated on a match
+    Match(Box<Expression>, Vec<(Pattern, Expression)>),
+    //.. Propagate: `Ok`/`Err` or `Some`/`None` value followed by `?`
+    Propagate(Box<Expression>),
+    //.. Throw: thrown value
+    Throw(Box<Expression>),
+    //.. TryCatch: try block, name of caught error, catch block, finally block
+    //   (if given)
+    TryCatch(Box<Expression>, String, Box<Expression>, Box<Option<Expression>>),
+    IntegerValue(i64),
     StringValue(String),
     BooleanValue(bool),
     Identifier(String),
@@ -20,12 +53,28 @@ pub enum Expression {
     IfExpression(Box<Expression>, Box<Expression>, Box<Expression>),
     //.. While: condition, body
     While(Box<Expression>, Box<Expression>),
-    //.. Fn: argument name (if given), function body
-    Fn(Option<String>, Box<Expression>),
+    //.. And: operands, evaluated until one is false
+    And(Vec<Expression>),
+    //.. Or: operands, evaluated until one is true
+    Or(Vec<Expression>),
+    //.. Fn: parameters, rest parameter name (if given), function body, the
+    //   scope it was created in (once evaluated)
+    Fn(Vec<Parameter>, Option<String>, Box<Expression>, Option<Rc<EvaluationScope>>),
     //.. LetBinding: variable name, value
     LetBinding(String, Box<Expression>),
-    //.. FnCall: function, argument (if given)
-    FnCall(Box<Expression>, Box<Option<Expression>>),
+    //.. FnDeclaration: function name, function
+    FnDeclaration(String, Box<Expression>),
+    //.. Import: path of the imported file, namespace name (if given)
+    Import(String, Option<String>),
+    //.. FnCall: function, positional arguments, named arguments
+    FnCall(Box<Expression>, Vec<Expression>, Vec<(String, Expression)>),
+    //.. PartialApplication: function, the (evaluated) positional and named
+    //   arguments applied so far
+    PartialApplication(Box<Expression>, Vec<Expression>, Vec<(String, Expression)>),
+    //.. Index: indexed list, string or map, index or key
+    Index(Box<Expression>, Box<Expression>),
+    //.. Slice: sliced list or string, start (if given), end (if given)
+    Slice(Box<Expression>, Box<Option<Expression>>, Box<Option<Expression>>),
     Block(Vec<Expression>),
     Program(Vec<Expression>),
     //.. BuiltInFn: argument length, function
@@ -38,6 +87,13 @@ impl PartialEq for Expression {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::List(a), Self::List(b)) => a == b,
+            (Self::Map(a), Self::Map(b)) => a == b,
+            (Self::Record(a), Self::Record(b)) => {
+                a.len() == b.len() && a.iter().all(|(name, value)| {
+                    b.iter().any(|(other_name, other_value)| name == other_name && value == other_value)
+                })
+            },
+            (Self::Variant(a_name, a), Self::Variant(b_name, b)) => a_name == b_name && a == b,
             (Self::StringValue(a), Self::StringValue(b)) => a == b,
             (Self::IntegerValue(a), Self::IntegerValue(b)) => a == b,
             (Self::BooleanValue(a), Self::BooleanValue(b)) => a == b,
@@ -61,17 +117,93 @@ impl std::fmt::Display for Expression {
                         .join(", ")
                 )
             },
-            Self::IntegerValue(n) => n.to_string(),
-            Self::Program(expressions) => {
+            Self::MapLiteral(entries) => {
                 format!(
-                    "{}",
-                    expressions
+                    "#{{{}}}",
+                    entries
                         .iter()
-                        .map(|expression| expression.to_string() + ";")
+                        .map(|(key, value)| format!("{}: {}", key, value))
                         .collect::<Vec<String>>()
-                        .join("\n")
+                        .join(", ")
+                )
+            },
+            Self::Map(entries) => {
+                format!(
+                    "#{{{}}}",
+                    entries
+                        .iter()
+                        .map(|(key, value)| format!("{}: {}", key.to_expression(), value))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            },
+            Self::Record(fields) => Self::fields_to_string("", fields),
+            Self::RecordUpdate(record, fields) => {
+                Self::fields_to_string(&format!("..{}", record), fields)
+            },
+            Self::FieldAccess(record, field_name) => format!("{}.{}", record, field_name),
+            Self::TypeDeclaration(type_name, constructors) => {
+                format!(
+                    "type {} = {}",
+                    type_name,
+                    constructors
+                        .iter()
+                        .map(|(name, field_names)| {
+                            if field_names.is_empty() {
+                                name.clone()
+                            } else {
+                                format!("{}({})", name, field_names.join(", "))
+                            }
+                        })
+                        .collect::<Vec<String>>()
+                        .join(" | ")
+                )
+            },
+            Self::Variant(name, values) => {
+                if values.is_empty() {
+                    name.clone()
+                } else {
+                    format!(
+                        "{}({})",
+                        name,
+                        values
+                            .iter()
+                            .map(|value| value.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )
+                }
+            },
+            Self::Match(value, arms) => {
+                format!(
+                    "match {} {{ {} }}",
+                    value,
+                    arms
+                        .iter()
+                        .map(|(pattern, body)| format!("{} => {}", pattern, body))
+                        .collect::<Vec<String>>()
+                        .join(", ")
                 )
             },
+            Self::Propagate(value) => format!("{}?", value),
+            Self::Throw(value) => format!("throw {}", value),
+            Self::TryCatch(try_block, error_name, catch_block, finally_block) => {
+                format!(
+                    "try {} catch {} {}{}",
+                    try_block,
+                    error_name,
+                    catch_block,
+                    finally_block.clone().map(|block| format!(" finally {}", block)).unwrap_or_default(),
+                )
+            },
+            Self::IntegerValue(n) => n.to_string(),
+            Self::Program(expressions) => {
+                expressions
+                    .iter()
+                    .map(|expression| expression.to_string() + ";")
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            },
             Self::IfExpression(condition, if_true, if_false) => {
                 format!(
                     "if {} {} else {}",
@@ -91,24 +223,57 @@ impl std::fmt::Display for Expression {
             Self::StringValue(value) => format!("\"{}\"", value),
             Self::BooleanValue(value) => value.to_string(),
             Self::Identifier(name) => name.clone(),
-            Self::Fn(argument_name, body) => {
-                format!(
-                    "fn ({}) {}",
-                    argument_name.clone().unwrap_or_default(),
-                    body,
-                )
+            Self::Fn(parameters, rest_parameter, body, _) => {
+                let mut parameter_strings: Vec<String> = parameters
+                    .iter()
+                    .map(|parameter| parameter.to_string())
+                    .collect();
+
+                if let Some(rest_parameter) = rest_parameter {
+                    parameter_strings.push(format!("..{}", rest_parameter));
+                }
+
+                format!("fn ({}) {}", parameter_strings.join(", "), body)
             },
             Self::LetBinding(variable, value) => {
                 format!("let {} = {}", variable, value)
             },
+            Self::FnDeclaration(fn_name, function) => {
+                format!("fn {}{}", fn_name, function.to_string().trim_start_matches("fn "))
+            },
+            Self::Import(path, namespace_name) => {
+                format!(
+                    "import \"{}\"{}",
+                    path,
+                    namespace_name.clone().map(|name| format!(" as {}", name)).unwrap_or_default(),
+                )
+            },
             Self::While(condition, body) => {
                 format!("while {} {}", condition, body)
             },
-            Self::FnCall(name, argument) => {
+            Self::And(operands) => format!("and({})", Self::arguments_to_string(operands, &[])),
+            Self::Or(operands) => format!("or({})", Self::arguments_to_string(operands, &[])),
+            Self::FnCall(function, arguments, named_arguments) => {
                 format!(
-                    "({}({}))",
-                    name,
-                    argument.clone().map(|name| name.to_string()).unwrap_or_default()
+                    "{}({})",
+                    function,
+                    Self::arguments_to_string(arguments, named_arguments),
+                )
+            },
+            Self::PartialApplication(function, arguments, named_arguments) => {
+                format!(
+                    "<{} applied to {}>",
+                    function,
+                    Self::arguments_to_string(arguments, named_arguments),
+                )
+            },
+            Self::Index(value, index) => format!("{}[{}]", value, index),
+            Self::Slice(value, start, end) => {
+                format!(
+                    "{}[{}..{}]",
+                    value,
+                    start.clone().map(|start| start.to_string()).unwrap_or_default(),
+                    end.clone().map(|end| end.to_string()).unwrap_or_default(),
                 )
             },
             Self::Null => "<null>".to_string(),
@@ -120,122 +285,445 @@ impl std::fmt::Display for Expression {
     }
 }
 
-struct EvaluationScope {
-    variables: HashMap<String, Expression>,
+#[derive(Clone)]
+pub struct Parameter {
+    pub name: String,
+    //.. Parameters with a default value may be left out when calling.
+    pub default_value: Option<Expression>,
 }
 
-impl EvaluationScope {
-    pub fn new_default() -> Self {
-        Self {
-            variables: builtin_functions::builtin_functions(),
+impl std::fmt::Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.default_value {
+            Some(default_value) => write!(f, "{} = {}", self.name, default_value),
+            None => write!(f, "{}", self.name),
         }
     }
+}
 
-    pub fn new_empty() -> Self {
-        Self {
-            variables: HashMap::new(),
+#[derive(Clone)]
+pub enum Pattern {
+    //.. Wildcard: `_`, matches anything
+    Wildcard,
+    //.. Binding: matches anything and binds it to the given name
+    Binding(String),
+    //.. Literal: matches values equal to the given integer, string or boolean
+    Literal(Expression),
+    //.. Constructor: constructor name, patterns for each of its fields
+    Constructor(String, Vec<Pattern>),
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let pattern_as_str = match self {
+            Self::Wildcard => "_".to_string(),
+            Self::Binding(name) => name.clone(),
+            Self::Literal(value) => value.to_string(),
+            Self::Constructor(name, field_patterns) => {
+                if field_patterns.is_empty() {
+                    name.clone()
+                } else {
+                    format!(
+                        "{}({})",
+                        name,
+                        field_patterns
+                            .iter()
+                            .map(|pattern| pattern.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )
+                }
+            },
+        };
+
+        write!(f, "{}", pattern_as_str)
+    }
+}
+
+impl Pattern {
+    //.. Names starting with an uppercase letter refer to constructors, both in
+    //   type declarations and in patterns, while other names are bindings.
+    pub fn is_constructor_name(name: &str) -> bool {
+        name.chars().next().is_some_and(|ch| ch.is_uppercase())
+    }
+
+    //.. Checks whether `value` matches this pattern. The values matched by
+    //   binding patterns are pushed onto `bindings`. A constructor pattern
+    //   with a different number of fields than the value doesn't match it.
+    fn matches(&self, value: &Expression, bindings: &mut Vec<(String, Expression)>) -> bool {
+        match self {
+            Self::Wildcard => true,
+            Self::Binding(name) => {
+                bindings.push((name.clone(), value.clone()));
+                true
+            },
+            Self::Literal(literal) => literal == value,
+            Self::Constructor(name, field_patterns) => {
+                let field_values = match value {
+                    Expression::Variant(variant_name, field_values) if variant_name == name => field_values,
+                    _ => return false,
+                };
+
+                field_patterns.len() == field_values.len()
+                    && field_patterns
+                        .iter()
+                        .zip(field_values)
+                        .all(|(field_pattern, field_value)| field_pattern.matches(field_value, bindings))
+            },
+        }
+    }
+}
+
+//.. The key of a map entry: a value which can be ordered and hashed, which
+//   functions (and maps themselves) can't.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MapKey {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+    List(Vec<MapKey>),
+    //.. Record: fields sorted by name, as the order of fields doesn't matter
+    //   for equality
+    Record(Vec<(String, MapKey)>),
+    Variant(String, Vec<MapKey>),
+}
+
+impl MapKey {
+    pub fn to_expression(&self) -> Expression {
+        match self {
+            Self::Null => Expression::Null,
+            Self::Boolean(b) => Expression::BooleanValue(*b),
+            Self::Integer(n) => Expression::IntegerValue(*n),
+            Self::String(string) => Expression::StringValue(string.clone()),
+            Self::List(items) => Expression::List(items.iter().map(Self::to_expression).collect()),
+            Self::Record(fields) => Expression::Record(
+                fields.iter().map(|(name, value)| (name.clone(), value.to_expression())).collect()
+            ),
+            Self::Variant(name, values) => Expression::Variant(
+                name.clone(), values.iter().map(Self::to_expression).collect()
+            ),
         }
     }
 }
 
+impl TryFrom<&Expression> for MapKey {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: &Expression) -> Result<Self, Self::Error> {
+        let key_list = |items: &[Expression]| items.iter().map(Self::try_from).collect::<Result<Vec<_>, _>>();
+
+        Ok(match value {
+            Expression::Null => Self::Null,
+            Expression::BooleanValue(b) => Self::Boolean(*b),
+            Expression::IntegerValue(n) => Self::Integer(*n),
+            Expression::StringValue(string) => Self::String(string.clone()),
+            Expression::List(items) => Self::List(key_list(items)?),
+            Expression::Record(fields) => {
+                let mut key_fields = Vec::new();
+
+                for (name, value) in fields {
+                    key_fields.push((name.clone(), Self::try_from(value)?));
+                }
+
+                key_fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+                Self::Record(key_fields)
+            },
+            Expression::Variant(name, values) => Self::Variant(name.clone(), key_list(values)?),
+            _ => return Err(RuntimeError::new(
+                ErrorKind::Type, format!("`{}` can't be used as a map key", value)
+            ).into()),
+        })
+    }
+}
+
+//.. Scopes are shared, as functions keep the scope they were created in,
+//   so a function returned from another function can still refer to the
+//   variables of the function it was returned from.
+pub struct EvaluationScope {
+    variables: RefCell<HashMap<String, Expression>>,
+    //.. The scope this one is nested in, where variables that aren't found
+    //   in this scope are looked up.
+    parent: Option<Rc<EvaluationScope>>,
+    //.. A scope holding only the names bound by a pattern or `catch`, where
+    //   `let` binds other names in the scope this one is nested in, just like
+    //   in a block that isn't scoped.
+    is_bindings_scope: bool,
+}
+
+impl EvaluationScope {
+    pub fn new_default() -> Rc<Self> {
+        Rc::new(Self {
+            variables: RefCell::new(builtin_functions::builtin_functions()),
+            parent: None,
+            is_bindings_scope: false,
+        })
+    }
+
+    pub fn new_nested(parent: &Rc<Self>) -> Rc<Self> {
+        Rc::new(Self {
+            variables: RefCell::new(HashMap::new()),
+            parent: Some(parent.clone()),
+            is_bindings_scope: false,
+        })
+    }
+
+    pub fn new_bindings(parent: &Rc<Self>, bindings: Vec<(String, Expression)>) -> Rc<Self> {
+        Rc::new(Self {
+            variables: RefCell::new(bindings.into_iter().collect()),
+            parent: Some(parent.clone()),
+            is_bindings_scope: true,
+        })
+    }
+}
+
 pub struct EvaluationContext {
-    scopes: Vec<EvaluationScope>,
+    //.. The scope of the built-in functions (and the prelude), which all
+    //   other scopes are nested in.
+    builtins_scope: Rc<EvaluationScope>,
+    scope: Rc<EvaluationScope>,
+    //.. The file of the program, followed by the files of the modules that
+    //   are currently being imported, innermost last.
+    module_files: Vec<PathBuf>,
+    //.. Namespaces of the modules imported so far, by their (canonical) path.
+    modules: HashMap<PathBuf, Expression>,
+    pub random: Random,
+    //.. What the program is allowed to access, checked by built-in functions.
+    pub capabilities: Capabilities,
+    pub budget: Budget,
 }
 
 impl EvaluationContext {
-    pub fn new() -> Self {
-        let mut ctx = Self {
-            scopes: vec![EvaluationScope::new_default()],
-        };
-        ctx.enter_scope();
-        ctx
+    pub fn new(capabilities: Capabilities, limits: Limits) -> Self {
+        let builtins_scope = EvaluationScope::new_default();
+
+        Self {
+            //.. The global scope, so bindings made at the top level of a
+            //   program don't end up between the built-in functions.
+            scope: EvaluationScope::new_nested(&builtins_scope),
+            builtins_scope,
+            module_files: Vec::new(),
+            modules: HashMap::new(),
+            random: Random::from_time(),
+            capabilities,
+            budget: Budget::new(limits),
+        }
     }
 
-    pub fn enter_scope(&mut self) {
-        self.scopes.push(EvaluationScope::new_empty());
+    //.. Enters a scope holding only `bindings`, which is left by `exit_scope`.
+    pub fn enter_bindings_scope(&mut self, bindings: Vec<(String, Expression)>) {
+        self.scope = EvaluationScope::new_bindings(&self.scope, bindings);
     }
 
     pub fn exit_scope(&mut self) {
-        if self.scopes.len() > 1 {
-            self.scopes.pop();
+        if let Some(parent) = self.scope.parent.clone() {
+            self.scope = parent;
         }
     }
 
+    //.. Enters the scope of a function call, nested in the scope the function
+    //   was created in instead of the scope of the caller. Gives back the
+    //   scope of the caller, to be restored by `exit_fn_scope`.
+    fn enter_fn_scope(&mut self, fn_scope: Option<&Rc<EvaluationScope>>) -> Rc<EvaluationScope> {
+        let fn_scope = EvaluationScope::new_nested(fn_scope.unwrap_or(&self.scope));
+
+        std::mem::replace(&mut self.scope, fn_scope)
+    }
+
+    fn exit_fn_scope(&mut self, caller_scope: Rc<EvaluationScope>) {
+        self.scope = caller_scope;
+    }
+
     pub fn resolve_var(&self, var_name: String) -> Option<Expression> {
-        for scope in self.scopes.iter().rev() {
-            match scope.variables.get(&var_name) {
-                Some(var) => return Some(var.clone()),
-                None => (),
-            };
+        let mut scope = Some(&self.scope);
+
+        while let Some(current_scope) = scope {
+            if let Some(var) = current_scope.variables.borrow().get(&var_name) {
+                return Some(var.clone());
+            }
+
+            scope = current_scope.parent.as_ref();
         }
 
         None
     }
 
-    fn outer_scope(&mut self) -> &mut EvaluationScope {
-        self.scopes.iter_mut().rev().nth(1).unwrap()
+    //.. Binds a variable in the current scope, or for names a bindings scope
+    //   doesn't hold, in the first scope it's nested in that isn't one.
+    pub fn add_local_var(&mut self, var_name: String, value: Expression) {
+        let mut scope = &self.scope;
+
+        while scope.is_bindings_scope && !scope.variables.borrow().contains_key(&var_name) {
+            match &scope.parent {
+                Some(parent) => scope = parent,
+                None => break,
+            }
+        }
+
+        scope.variables.borrow_mut().insert(var_name, value);
     }
 
-    pub fn add_local_var(&mut self, var_name: String, value: Expression) {
-        self.outer_scope().variables.insert(var_name, value);
+    //.. Evaluates the prelude in the scope of the built-in functions, so its
+    //   bindings are available everywhere, including in imported modules.
+    pub fn load_prelude(ctx: &SharedContext) -> Result<(), Box<dyn std::error::Error>> {
+        let builtins_scope = ctx.borrow().builtins_scope.clone();
+        let global_scope = std::mem::replace(&mut ctx.borrow_mut().scope, builtins_scope);
+
+        let result = Parser::new(builtin_functions::PRELUDE.to_string())
+            .parse_program()
+            .and_then(|program| program.evaluate(ctx.clone()))
+            .map_err(|error| format!("failed to load prelude: {}", error));
+
+        ctx.borrow_mut().scope = global_scope;
+
+        result?;
+        Ok(())
+    }
+
+    //.. Binds the command line arguments given after the file of the program
+    //   as `args`, next to the built-in functions.
+    pub fn set_program_arguments(&mut self, arguments: Vec<String>) {
+        self.builtins_scope.variables.borrow_mut().insert(
+            "args".to_string(),
+            Expression::List(arguments.into_iter().map(Expression::StringValue).collect()),
+        );
+    }
+
+    //.. Sets the file of the program, which imports are resolved relative to.
+    pub fn set_main_file(&mut self, path: PathBuf) {
+        self.module_files = vec![path.canonicalize().unwrap_or(path)];
+    }
+
+    //.. Modules are evaluated in their own global scope, so they only see the
+    //   built-in functions and not the bindings of the importing file. Gives
+    //   back the scope of the importing file, to be restored by `exit_module`.
+    fn enter_module(&mut self, module_path: PathBuf) -> Rc<EvaluationScope> {
+        let module_scope = EvaluationScope::new_nested(&self.builtins_scope);
+
+        self.module_files.push(module_path);
+        std::mem::replace(&mut self.scope, module_scope)
+    }
+
+    //.. Restores the scope of the importing file and gives back the top-level
+    //   bindings of the module. The functions of the module keep the scope of
+    //   the module, so they can use its other bindings without these having
+    //   to be exported by name.
+    fn exit_module(&mut self, importing_scope: Rc<EvaluationScope>) -> HashMap<String, Expression> {
+        self.module_files.pop();
+
+        let module_scope = std::mem::replace(&mut self.scope, importing_scope);
+        let module_bindings = module_scope.variables.borrow().clone();
+        module_bindings
     }
 }
 
-type SharedContext = Rc<RefCell<EvaluationContext>>;
+pub type SharedContext = Rc<RefCell<EvaluationContext>>;
 
 impl Expression {
-    pub fn integer_value(&self) -> Result<u64, Box<dyn std::error::Error>> {
+    pub fn integer_value(&self) -> Result<i64, Box<dyn std::error::Error>> {
         match &self {
             Self::IntegerValue(n) => Ok(*n),
-            _ => Err(format!("expected integer, got `{}`", self).into()),
+            _ => Err(RuntimeError::new(ErrorKind::Type, format!("expected integer, got `{}`", self)).into()),
         }
     }
 
     pub fn string_value(&self) -> Result<String, Box<dyn std::error::Error>> {
         match &self {
             Self::StringValue(n) => Ok(n.clone()),
-            _ => Err(format!("expected string, got `{}`", self).into()),
+            _ => Err(RuntimeError::new(ErrorKind::Type, format!("expected string, got `{}`", self)).into()),
         }
     }
 
     pub fn boolean_value(&self) -> Result<bool, Box<dyn std::error::Error>> {
         match &self {
             Self::BooleanValue(n) => Ok(*n),
-            _ => Err(format!("expected boolean, got `{}`", self).into()),
+            _ => Err(RuntimeError::new(ErrorKind::Type, format!("expected boolean, got `{}`", self)).into()),
         }
     }
 
     pub fn list_value(&self) -> Result<Vec<Expression>, Box<dyn std::error::Error>> {
         match &self {
             Self::List(n) => Ok(n.clone()),
-            _ => Err(format!("expected list, got `{}`", self).into()),
+            _ => Err(RuntimeError::new(ErrorKind::Type, format!("expected list, got `{}`", self)).into()),
         }
     }
 
-    pub fn identifier_name(&self) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn map_value(&self) -> Result<BTreeMap<MapKey, Expression>, Box<dyn std::error::Error>> {
         match &self {
-            Self::Identifier(name) => Ok(name.clone()),
-            _ => Err(format!("expected identifier, got `{}`", self).into()),
+            Self::Map(entries) => Ok(entries.clone()),
+            _ => Err(RuntimeError::new(ErrorKind::Type, format!("expected map, got `{}`", self)).into()),
         }
     }
 
-    pub fn is_null(&self) -> bool {
+    pub fn record_value(&self) -> Result<Vec<(String, Expression)>, Box<dyn std::error::Error>> {
         match &self {
-            Self::Null => true,
-            _ => false
+            Self::Record(fields) => Ok(fields.clone()),
+            _ => Err(RuntimeError::new(ErrorKind::Type, format!("expected record, got `{}`", self)).into()),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    fn arguments_to_string(arguments: &[Expression], named_arguments: &[(String, Expression)]) -> String {
+        arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .chain(
+                named_arguments
+                    .iter()
+                    .map(|(name, argument)| format!("{}: {}", name, argument))
+            )
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    //.. Formats the fields of a record, preceded by e.g. the record being
+    //   updated if `prefix` isn't empty.
+    fn fields_to_string(prefix: &str, fields: &[(String, Expression)]) -> String {
+        let mut parts: Vec<String> = fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+
+        if !prefix.is_empty() {
+            parts.insert(0, prefix.to_string());
+        }
+
+        if parts.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{ {} }}", parts.join(", "))
         }
     }
 
     fn evaluate_identifier(&self, ctx: SharedContext, identifier: String) -> Result<Expression, Box<dyn std::error::Error>> {
         match ctx.borrow().resolve_var(identifier.clone()) {
             Some(value) => Ok(value.clone()),
-            None => Err(format!("unknown identifier `{}`", identifier.clone()).into()),
+            None => Err(RuntimeError::new(
+                ErrorKind::Name, format!("unknown identifier `{}`", identifier.clone())
+            ).into()),
         }
     }
 
-    fn evaluate_block(&self, ctx: SharedContext, block_body: &Vec<Expression>) -> Result<Expression, Box<dyn std::error::Error>> {
+    //.. Binds all functions declared using `fn <name>(...)` before anything
+    //   else is evaluated, so they can be called before their declaration and
+    //   refer to each other regardless of the order they're declared in.
+    fn hoist_fn_declarations(ctx: SharedContext, expressions: &[Expression]) {
+        for expression in expressions {
+            if let Self::FnDeclaration(fn_name, function) = expression {
+                let scope = ctx.borrow().scope.clone();
+                ctx.borrow_mut().add_local_var(fn_name.clone(), function.clone().with_scope(&scope));
+            }
+        }
+    }
+
+    fn evaluate_block(&self, ctx: SharedContext, block_body: &[Expression]) -> Result<Expression, Box<dyn std::error::Error>> {
         let mut return_value = Self::Null;
 
+        Self::hoist_fn_declarations(ctx.clone(), block_body);
+
         for expression in block_body.iter() {
             return_value = expression.clone().evaluate(ctx.clone())?;
         }
@@ -243,11 +731,11 @@ impl Expression {
         Ok(return_value)
     }
 
-    fn evaluate_let_binding(&self, ctx: SharedContext, variable_name: &String, value: &Expression) -> Result<Expression, Box<dyn std::error::Error>> {
+    fn evaluate_let_binding(&self, ctx: SharedContext, variable_name: &str, value: &Expression) -> Result<Expression, Box<dyn std::error::Error>> {
         let evaluated_value = value.clone().evaluate(ctx.clone())?;
                 
         ctx.borrow_mut().add_local_var(
-            variable_name.clone(),
+            variable_name.to_string(),
             evaluated_value.clone()
         );
 
@@ -264,81 +752,629 @@ impl Expression {
         Ok(Expression::List(result_list))
     }
 
-    fn evaluate_fn_call(&self, ctx: SharedContext, function: Box<Expression>, argument_value_opt: &Option<Expression>) -> Result<Expression, Box<dyn std::error::Error>> {
-        let function_name = function.identifier_name().unwrap_or("<anonymous>".to_string());
+    fn evaluate_map(&self, ctx: SharedContext, entries: &[(Expression, Expression)]) -> Result<Expression, Box<dyn std::error::Error>> {
+        let mut result_entries = BTreeMap::new();
 
-        ctx.borrow_mut().enter_scope();
+        for (key, value) in entries {
+            let key = MapKey::try_from(&key.clone().evaluate(ctx.clone())?)?;
+            let value = value.clone().evaluate(ctx.clone())?;
 
-        let result = match function.clone().evaluate(ctx.clone())? {
-            Self::Fn(argument_name, body) => {
-                if argument_value_opt.is_none() != argument_name.is_none() {
-                    return Err(format!(
-                        "function `{}` expected {} arguments, got {} instead",
-                        function_name,
-                        if argument_name.is_some() { 1 } else { 0 },
-                        if argument_value_opt.is_some() { 1 } else { 0 },
-                    ).into())
+            result_entries.insert(key, value);
+        }
+
+        Ok(Expression::Map(result_entries))
+    }
+
+    fn evaluate_record(&self, ctx: SharedContext, fields: &[(String, Expression)]) -> Result<Expression, Box<dyn std::error::Error>> {
+        let mut result_fields = Vec::new();
+
+        for (name, value) in fields {
+            result_fields.push((name.clone(), value.clone().evaluate(ctx.clone())?));
+        }
+
+        Ok(Expression::Record(result_fields))
+    }
+
+    fn evaluate_record_update(&self, ctx: SharedContext, record: &Expression, fields: &[(String, Expression)]) -> Result<Expression, Box<dyn std::error::Error>> {
+        let evaluated_record = record.clone().evaluate(ctx.clone())?;
+        let mut result_fields = evaluated_record.record_value()?;
+
+        for (name, value) in fields {
+            let value = value.clone().evaluate(ctx.clone())?;
+
+            match result_fields.iter_mut().find(|(field_name, _)| field_name == name) {
+                Some(field) => field.1 = value,
+                None => {
+                    return Err(RuntimeError::new(ErrorKind::Key, format!(
+                        "record `{}` has no field `{}` to update",
+                        evaluated_record, name
+                    )).into());
+                },
+            }
+        }
+
+        Ok(Expression::Record(result_fields))
+    }
+
+    fn evaluate_field_access(&self, ctx: SharedContext, record: &Expression, field_name: &str) -> Result<Expression, Box<dyn std::error::Error>> {
+        match record.clone().evaluate(ctx.clone())? {
+            Self::Record(fields) => {
+                match fields.into_iter().find(|(name, _)| name == field_name) {
+                    Some((_, value)) => Ok(value),
+                    None => Err(RuntimeError::new(
+                        ErrorKind::Key, format!("record has no field `{}`", field_name)
+                    ).into()),
                 }
+            },
+            other => Err(RuntimeError::new(ErrorKind::Type, format!(
+                "trying to access field `{}` of `{}`, which is not a record",
+                field_name, other
+            )).into()),
+        }
+    }
+
+    fn evaluate_type_declaration(&self, ctx: SharedContext, constructors: &[(String, Vec<String>)]) -> Result<Expression, Box<dyn std::error::Error>> {
+        for (name, field_names) in constructors {
+            //.. Constructors without fields are values, the others are
+            //   functions returning a variant containing their arguments.
+            let constructor = if field_names.is_empty() {
+                Self::Variant(name.clone(), Vec::new())
+            } else {
+                let field_count = u8::try_from(field_names.len()).map_err(|_| {
+                    format!("constructor `{}` has more than {} fields", name, u8::MAX)
+                })?;
+                let variant_name = name.clone();
+
+                Self::BuiltInFn(field_count, Rc::new(move |_ctx, items| {
+                    Ok(Self::Variant(variant_name.clone(), items))
+                }))
+            };
+
+            ctx.borrow_mut().add_local_var(name.clone(), constructor);
+        }
+
+        Ok(Expression::Null)
+    }
+
+    fn evaluate_match(&self, ctx: SharedContext, value: &Expression, arms: &[(Pattern, Expression)]) -> Result<Expression, Box<dyn std::error::Error>> {
+        let evaluated_value = value.clone().evaluate(ctx.clone())?;
+
+        for (pattern, body) in arms {
+            let mut bindings = Vec::new();
+
+            if pattern.matches(&evaluated_value, &mut bindings) {
+                //.. The bindings of an arm only exist in its own scope, so they
+                //   don't replace variables outside of the match expression.
+                ctx.borrow_mut().enter_bindings_scope(bindings);
 
-                if let Some(argument_value) = argument_value_opt {
-                    let value = argument_value.clone().evaluate(ctx.clone())?.clone();
+                let result = body.clone().evaluate(ctx.clone());
 
-                    ctx.borrow_mut().add_local_var(
-                        argument_name.unwrap().clone(), value
-                    );
+                ctx.borrow_mut().exit_scope();
+                return result;
+            }
+        }
+
+        Err(RuntimeError::new(
+            ErrorKind::Match, format!("no arm of match expression matches `{}`", evaluated_value)
+        ).into())
+    }
+
+    fn evaluate_propagate(&self, ctx: SharedContext, value: &Expression) -> Result<Expression, Box<dyn std::error::Error>> {
+        match value.clone().evaluate(ctx.clone())? {
+            Self::Variant(name, mut values) if (name == "Ok" || name == "Some") && values.len() == 1 => {
+                Ok(values.remove(0))
+            },
+            failure @ Self::Variant(_, _) if failure.is_failure_variant() => {
+                Err(Box::new(EarlyReturn(failure)))
+            },
+            other => Err(RuntimeError::new(ErrorKind::Type, format!(
+                "`?` can only be applied to `Ok`, `Err`, `Some` or `None`, got `{}`",
+                other
+            )).into()),
+        }
+    }
+
+    fn is_failure_variant(&self) -> bool {
+        match self {
+            Self::Variant(name, values) => {
+                (name == "Err" && values.len() == 1) || (name == "None" && values.is_empty())
+            },
+            _ => false,
+        }
+    }
+
+    //.. The name a called function is referred to by, used in errors.
+    fn callee_name(function: &Expression) -> String {
+        match function {
+            Self::Identifier(name) => name.clone(),
+            Self::FieldAccess(record, field_name) => {
+                format!("{}.{}", Self::callee_name(record), field_name)
+            },
+            _ => "<anonymous>".to_string(),
+        }
+    }
+
+    //.. The number of arguments a function still has to be given before its
+    //   body is evaluated, and the maximum number of positional arguments it
+    //   still accepts, which is `None` when it has a rest parameter.
+    fn remaining_parameters(&self) -> Result<(usize, Option<usize>), Box<dyn std::error::Error>> {
+        match self {
+            Self::Fn(parameters, rest_parameter, _, _) => Ok((
+                parameters.iter().filter(|parameter| parameter.default_value.is_none()).count(),
+                if rest_parameter.is_some() { None } else { Some(parameters.len()) },
+            )),
+            Self::BuiltInFn(argument_length, _) => {
+                Ok((*argument_length as usize, Some(*argument_length as usize)))
+            },
+            Self::PartialApplication(function, arguments, named_arguments) => {
+                match function.as_ref() {
+                    Self::Fn(parameters, rest_parameter, _, _) => {
+                        let required = parameters
+                            .iter()
+                            .skip(arguments.len())
+                            .filter(|parameter| {
+                                parameter.default_value.is_none()
+                                    && !named_arguments.iter().any(|(name, _)| *name == parameter.name)
+                            })
+                            .count();
+                        let maximum = match rest_parameter {
+                            Some(_) => None,
+                            None => Some(parameters.len().saturating_sub(arguments.len())),
+                        };
+
+                        Ok((required, maximum))
+                    },
+                    _ => {
+                        let (required, maximum) = function.remaining_parameters()?;
+
+                        Ok((
+                            required - arguments.len(),
+                            maximum.map(|maximum| maximum - arguments.len()),
+                        ))
+                    },
                 }
+            },
+            other => Err(RuntimeError::new(
+                ErrorKind::Type, format!("expected function, got `{}`", other)
+            ).into()),
+        }
+    }
+
+    //.. The number of arguments a function still has to be given before its
+    //   body is evaluated, not counting parameters with a default value.
+    pub fn arity(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        Ok(self.remaining_parameters()?.0)
+    }
+
+    fn describe_arity(required: usize, maximum: Option<usize>) -> String {
+        let plural = |n| if n == 1 { "" } else { "s" };
 
-                body.clone().evaluate(ctx.clone())
+        match maximum {
+            Some(maximum) if maximum == required => format!("{} argument{}", required, plural(required)),
+            Some(maximum) => format!("{} to {} arguments", required, maximum),
+            None => format!("at least {} argument{}", required, plural(required)),
+        }
+    }
+
+    //.. Handles an error raised in the body of a user-defined function: a
+    //   value propagated by `?` becomes the return value of the function, and
+    //   runtime errors get the function added to their call stack.
+    fn handle_fn_body_error(function_name: &str, error: Box<dyn std::error::Error>) -> Result<Expression, Box<dyn std::error::Error>> {
+        let error = match error.downcast::<EarlyReturn>() {
+            Ok(early_return) => return Ok(early_return.0),
+            Err(error) => error,
+        };
+
+        match RuntimeError::from_error(error) {
+            Ok(mut runtime_error) => {
+                runtime_error.call_stack.push(function_name.to_string());
+                Err(runtime_error.into())
             },
+            Err(signal) => Err(signal),
+        }
+    }
+
+    fn evaluate_fn_call(&self, ctx: SharedContext, function: &Expression, arguments: &[Expression], named_arguments: &[(String, Expression)]) -> Result<Expression, Box<dyn std::error::Error>> {
+        let function_name = Self::callee_name(function);
+        let evaluated_function = function.clone().evaluate(ctx.clone())?;
+
+        let mut argument_values = Vec::new();
+
+        for argument in arguments {
+            argument_values.push(argument.clone().evaluate(ctx.clone())?);
+        }
+
+        let mut named_argument_values = Vec::new();
+
+        for (name, argument) in named_arguments {
+            named_argument_values.push((name.clone(), argument.clone().evaluate(ctx.clone())?));
+        }
+
+        Self::call_function(
+            ctx, &function_name, evaluated_function, argument_values, named_argument_values
+        )
+    }
+
+    //.. Calls an evaluated function with evaluated arguments. Giving a
+    //   function fewer arguments than it requires results in a partial
+    //   application, which takes the remaining arguments once it's called.
+    pub fn call_function(ctx: SharedContext, function_name: &str, function: Expression, argument_values: Vec<Expression>, named_argument_values: Vec<(String, Expression)>) -> Result<Expression, Box<dyn std::error::Error>> {
+        let (required, maximum) = function.remaining_parameters().map_err(|_| {
+            RuntimeError::new(
+                ErrorKind::Type, format!("trying to call `{}`, which is not a function", function)
+            )
+        })?;
 
+        let is_over_applied = maximum.is_some_and(|maximum| argument_values.len() > maximum);
+        let is_applied_to_nothing = argument_values.is_empty() && named_argument_values.is_empty();
+
+        if is_over_applied || (is_applied_to_nothing && required > 0) {
+            return Err(RuntimeError::new(ErrorKind::Arity, format!(
+                "`{}` takes {}, got {}",
+                function_name,
+                Self::describe_arity(required, maximum),
+                argument_values.len(),
+            )).into());
+        }
+
+        let (function, argument_values, named_argument_values) = match function {
+            Self::PartialApplication(function, mut applied_values, mut applied_named_values) => {
+                applied_values.extend(argument_values);
+                applied_named_values.extend(named_argument_values);
+                (*function, applied_values, applied_named_values)
+            },
+            function => (function, argument_values, named_argument_values),
+        };
+
+        match &function {
+            Self::Fn(parameters, rest_parameter, body, fn_scope) => {
+                Self::check_named_arguments(
+                    function_name, parameters, argument_values.len(), &named_argument_values
+                )?;
+
+                let is_missing_arguments = parameters
+                    .iter()
+                    .skip(argument_values.len())
+                    .any(|parameter| {
+                        parameter.default_value.is_none()
+                            && !named_argument_values.iter().any(|(name, _)| *name == parameter.name)
+                    });
+
+                if is_missing_arguments {
+                    return Ok(Self::PartialApplication(
+                        Box::new(function.clone()), argument_values, named_argument_values
+                    ));
+                }
+
+                interrupt::check()?;
+                ctx.borrow_mut().budget.enter_call()?;
+                let caller_scope = ctx.borrow_mut().enter_fn_scope(fn_scope.as_ref());
+
+                let result = Self::bind_arguments(
+                    ctx.clone(), parameters, rest_parameter, argument_values, named_argument_values
+                )
+                    .and_then(|_| body.clone().evaluate(ctx.clone()))
+                    .or_else(|error| Self::handle_fn_body_error(function_name, error));
+
+                ctx.borrow_mut().exit_fn_scope(caller_scope);
+                ctx.borrow_mut().budget.exit_call();
+                result
+            },
             Self::BuiltInFn(argument_length, closure_fn) => {
-                if argument_length != 0 && argument_value_opt.is_none() {
-                    Err(format!(
-                        "function `{}` expected {} arguments, got {} instead",
-                        function_name,
-                        argument_length,
-                        if argument_value_opt.is_some() { 1 } else { 0 },
-                    ).into())
-                
-                //.. In the case of a built-in function accepting multiple
-                //   arguments, each time an argument is applied, a
-                //   BuiltInFunction is returned with a new closure, containing
-                //   the applied argument. This is the necessary because all
-                //   functions are unary functions, which allows for the
-                //   support of partial-application.
-                } else if argument_length > 1 {
-                    let argument_value = argument_value_opt.clone().unwrap();
-                    Ok(Self::BuiltInFn(argument_length - 1, Rc::new(move |ctx, mut items| {
-                        items.insert(0, argument_value.clone().evaluate(ctx.clone())?);
-
-                        closure_fn(ctx.clone(), items)
-                    })))
-
-                //.. If there are one or zero arguments applied, call the
-                //   closure function.
-                } else if let Some(argument_value) = argument_value_opt {
-                    closure_fn(
-                        ctx.clone(),
-                        vec![argument_value.clone().evaluate(ctx.clone())?],
-                    )
-                } else {
-                    closure_fn(ctx.clone(), Vec::new())
+                if !named_argument_values.is_empty() {
+                    return Err(RuntimeError::new(ErrorKind::Arity, format!(
+                        "built-in function `{}` doesn't take named arguments",
+                        function_name
+                    )).into());
                 }
+
+                if argument_values.len() < *argument_length as usize {
+                    return Ok(Self::PartialApplication(
+                        Box::new(function.clone()), argument_values, Vec::new()
+                    ));
+                }
+
+                closure_fn(ctx.clone(), argument_values)
+            },
+            _ => unreachable!("only functions have parameters"),
+        }
+    }
+
+    fn check_named_arguments(function_name: &str, parameters: &[Parameter], argument_count: usize, named_argument_values: &[(String, Expression)]) -> Result<(), Box<dyn std::error::Error>> {
+        for (index, (name, _)) in named_argument_values.iter().enumerate() {
+            let is_given_twice = named_argument_values[..index]
+                .iter()
+                .any(|(other_name, _)| other_name == name);
+
+            match parameters.iter().position(|parameter| parameter.name == *name) {
+                None => {
+                    return Err(RuntimeError::new(ErrorKind::Arity, format!(
+                        "`{}` has no parameter named `{}`",
+                        function_name, name
+                    )).into());
+                },
+                Some(position) if position < argument_count || is_given_twice => {
+                    return Err(RuntimeError::new(ErrorKind::Arity, format!(
+                        "`{}` got more than one value for parameter `{}`",
+                        function_name, name
+                    )).into());
+                },
+                Some(_) => (),
             }
+        }
 
-            other => {
-                Err(format!("trying to call `{}`, which is not a function", other).into())
+        Ok(())
+    }
+
+    //.. Binds the arguments of a call in the current scope. Parameters which
+    //   weren't given an argument get their default value, which is evaluated
+    //   after the parameters before it have been bound, so it can refer to
+    //   them. Positional arguments beyond the parameters go in the rest
+    //   parameter as a list.
+    fn bind_arguments(ctx: SharedContext, parameters: &[Parameter], rest_parameter: &Option<String>, mut argument_values: Vec<Expression>, named_argument_values: Vec<(String, Expression)>) -> Result<(), Box<dyn std::error::Error>> {
+        let rest_values = argument_values.split_off(parameters.len().min(argument_values.len()));
+        let mut argument_values = argument_values.into_iter();
+
+        for parameter in parameters {
+            let named_value = named_argument_values
+                .iter()
+                .find(|(name, _)| *name == parameter.name)
+                .map(|(_, value)| value.clone());
+
+            let value = if let Some(value) = argument_values.next() {
+                value
+            } else if let Some(value) = named_value {
+                value
+            } else if let Some(default_value) = &parameter.default_value {
+                default_value.clone().evaluate(ctx.clone())?
+            } else {
+                unreachable!("calls with missing arguments are partial applications")
+            };
+
+            ctx.borrow_mut().add_local_var(parameter.name.clone(), value);
+        }
+
+        if let Some(rest_parameter) = rest_parameter {
+            ctx.borrow_mut().add_local_var(rest_parameter.clone(), Self::List(rest_values));
+        }
+
+        Ok(())
+    }
+
+    //.. Imports a module, binding its namespace: a record of its top-level
+    //   bindings. A module is only evaluated the first time it's imported,
+    //   later imports give back the same namespace.
+    fn evaluate_import(&self, ctx: SharedContext, path: &str, namespace_name: &Option<String>) -> Result<Expression, Box<dyn std::error::Error>> {
+        let importing_file = ctx.borrow().module_files.last().cloned();
+        let module_path = module::resolve_path(path, importing_file.as_deref())?;
+
+        ctx.borrow().capabilities.check_path(Capability::FsRead, "import", &module_path)?;
+
+        let namespace_name = match namespace_name {
+            Some(namespace_name) => namespace_name.clone(),
+            None => module::namespace_name(&module_path)?,
+        };
+
+        let cached_namespace = ctx.borrow().modules.get(&module_path).cloned();
+
+        let namespace = match cached_namespace {
+            Some(namespace) => namespace,
+            None => Self::load_module(ctx.clone(), module_path)?,
+        };
+
+        ctx.borrow_mut().add_local_var(namespace_name, namespace.clone());
+
+        Ok(namespace)
+    }
+
+    fn load_module(ctx: SharedContext, module_path: PathBuf) -> Result<Expression, Box<dyn std::error::Error>> {
+        let cycle_start = ctx.borrow().module_files.iter().position(|file| *file == module_path);
+
+        if let Some(cycle_start) = cycle_start {
+            let cycle = ctx.borrow().module_files[cycle_start..]
+                .iter()
+                .chain(std::iter::once(&module_path))
+                .map(|file| file.display().to_string())
+                .collect::<Vec<String>>()
+                .join(" -> ");
+
+            return Err(RuntimeError::new(
+                ErrorKind::Import, format!("import cycle: {}", cycle)
+            ).into());
+        }
+
+        let program = Parser::from_file(module_path.to_string_lossy().to_string())
+            .and_then(|mut parser| parser.parse_program())
+            .map_err(|error| RuntimeError::new(ErrorKind::Import, format!(
+                "failed to import `{}`: {}",
+                module_path.display(),
+                error,
+            )))?;
+
+        let importing_scope = ctx.borrow_mut().enter_module(module_path.clone());
+        let result = program.evaluate(ctx.clone());
+        let module_bindings = ctx.borrow_mut().exit_module(importing_scope);
+
+        result?;
+
+        let mut fields: Vec<(String, Expression)> = module_bindings.into_iter().collect();
+
+        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let namespace = Self::Record(fields);
+        ctx.borrow_mut().modules.insert(module_path, namespace.clone());
+
+        Ok(namespace)
+    }
+
+    //.. Gives back a function which keeps `scope`, the scope it's created in,
+    //   so its body can refer to the variables of that scope when called, even
+    //   after the scope was left. Functions which already have a scope keep it.
+    fn with_scope(self, scope: &Rc<EvaluationScope>) -> Expression {
+        match self {
+            Self::Fn(parameters, rest_parameter, body, None) => {
+                Self::Fn(parameters, rest_parameter, body, Some(scope.clone()))
             },
+            other => other,
+        }
+    }
+
+    fn evaluate_throw(&self, ctx: SharedContext, value: &Expression) -> Result<Expression, Box<dyn std::error::Error>> {
+        let evaluated_value = value.clone().evaluate(ctx.clone())?;
+
+        Err(Box::new(Thrown(evaluated_value)))
+    }
+
+    //.. The value bound by `catch`: either the value given to `throw`, or a
+    //   record describing a runtime error. Signals which shouldn't be caught,
+    //   like the one raised by `?`, are given back as `Err`.
+    fn caught_value(error: Box<dyn std::error::Error>) -> Result<Expression, Box<dyn std::error::Error>> {
+        let error = match error.downcast::<Thrown>() {
+            Ok(thrown) => return Ok(thrown.0),
+            Err(error) => error,
         };
 
-        ctx.borrow_mut().exit_scope();
+        //.. Interrupts can't be caught, so Ctrl-C can stop a loop containing
+        //   a `try` as well.
+        match RuntimeError::from_error(error) {
+            Ok(runtime_error) if runtime_error.kind == ErrorKind::Interrupted => Err(runtime_error.into()),
+            Ok(runtime_error) => Ok(runtime_error.to_expression()),
+            Err(signal) => Err(signal),
+        }
+    }
+
+    fn evaluate_try_catch(&self, ctx: SharedContext, try_block: &Expression, error_name: &str, catch_block: &Expression, finally_block: &Option<Expression>) -> Result<Expression, Box<dyn std::error::Error>> {
+        let result = match try_block.clone().evaluate(ctx.clone()) {
+            Err(error) => match Self::caught_value(error) {
+                Ok(caught_value) => {
+                    //.. The caught value is only bound inside the catch block.
+                    ctx.borrow_mut().enter_bindings_scope(vec![(error_name.to_string(), caught_value)]);
+
+                    let result = catch_block.clone().evaluate(ctx.clone());
+
+                    ctx.borrow_mut().exit_scope();
+                    result
+                },
+                Err(signal) => Err(signal),
+            },
+            result => result,
+        };
+
+        if let Some(finally_block) = finally_block {
+            finally_block.clone().evaluate(ctx.clone())?;
+        }
+
         result
     }
 
+    //.. Turns a possibly negative index into a position in a list or string of
+    //   `length` elements, where negative indices count from the end.
+    //   `allow_end` permits the position right after the last element, which
+    //   is used for slice bounds.
+    pub fn resolve_index(index: i64, length: usize, allow_end: bool) -> Result<usize, Box<dyn std::error::Error>> {
+        let position = if index < 0 { index + length as i64 } else { index };
+        let upper_bound = if allow_end { length as i64 } else { length as i64 - 1 };
+
+        if position < 0 || position > upper_bound {
+            return Err(RuntimeError::new(ErrorKind::Index, format!(
+                "index {} is out of range for a value containing {} elements",
+                index, length
+            )).into());
+        }
+
+        Ok(position as usize)
+    }
+
+    fn evaluate_index(&self, ctx: SharedContext, value: &Expression, index: &Expression) -> Result<Expression, Box<dyn std::error::Error>> {
+        let evaluated_value = value.clone().evaluate(ctx.clone())?;
+        let index = index.clone().evaluate(ctx.clone())?;
+
+        match evaluated_value {
+            Self::List(items) => {
+                let position = Self::resolve_index(index.integer_value()?, items.len(), false)?;
+                Ok(items[position].clone())
+            },
+            Self::StringValue(string) => {
+                let chars: Vec<char> = string.chars().collect();
+                let position = Self::resolve_index(index.integer_value()?, chars.len(), false)?;
+                Ok(Self::StringValue(chars[position].to_string()))
+            },
+            Self::Map(entries) => {
+                match entries.get(&MapKey::try_from(&index)?) {
+                    Some(value) => Ok(value.clone()),
+                    None => Err(RuntimeError::new(
+                        ErrorKind::Key, format!("key `{}` does not exist in map", index)
+                    ).into()),
+                }
+            },
+            other => Err(RuntimeError::new(
+                ErrorKind::Type,
+                format!("trying to index `{}`, which is not a list, string or map", other)
+            ).into()),
+        }
+    }
+
+    fn evaluate_slice(&self, ctx: SharedContext, value: &Expression, start: &Option<Expression>, end: &Option<Expression>) -> Result<Expression, Box<dyn std::error::Error>> {
+        let evaluated_value = value.clone().evaluate(ctx.clone())?;
+
+        let length = match &evaluated_value {
+            Self::List(items) => items.len(),
+            Self::StringValue(string) => string.chars().count(),
+            other => {
+                return Err(RuntimeError::new(
+                    ErrorKind::Type,
+                    format!("trying to slice `{}`, which is not a list or string", other)
+                ).into());
+            },
+        };
+
+        let start = match start {
+            Some(start) => {
+                let index = start.clone().evaluate(ctx.clone())?.integer_value()?;
+                Self::resolve_index(index, length, true)?
+            },
+            None => 0,
+        };
+        let end = match end {
+            Some(end) => {
+                let index = end.clone().evaluate(ctx.clone())?.integer_value()?;
+                Self::resolve_index(index, length, true)?
+            },
+            None => length,
+        };
+
+        if start > end {
+            return Err(RuntimeError::new(
+                ErrorKind::Index, format!("slice start {} is past slice end {}", start, end)
+            ).into());
+        }
+
+        Ok(match evaluated_value {
+            Self::List(items) => Self::List(items[start..end].to_vec()),
+            _ => Self::StringValue(
+                evaluated_value.string_value()?.chars().skip(start).take(end - start).collect()
+            ),
+        })
+    }
+
+    //.. Evaluates the operands of `and` (when `short_circuit_value` is false)
+    //   or `or` (when it's true), stopping at the first operand which equals
+    //   `short_circuit_value`.
+    fn evaluate_short_circuit(&self, ctx: SharedContext, operands: &[Expression], short_circuit_value: bool) -> Result<Expression, Box<dyn std::error::Error>> {
+        for operand in operands {
+            if operand.clone().evaluate(ctx.clone())?.boolean_value()? == short_circuit_value {
+                return Ok(Expression::BooleanValue(short_circuit_value));
+            }
+        }
+
+        Ok(Expression::BooleanValue(!short_circuit_value))
+    }
+
     pub fn evaluate_while(&self, ctx: SharedContext, condition: &Expression, body: &Expression) -> Result<Expression, Box<dyn std::error::Error>> {
         let mut result = Expression::Null;
 
         while condition.clone().evaluate(ctx.clone())?.boolean_value()? {
+            interrupt::check()?;
             result = body.clone().evaluate(ctx.clone())?;
         }
 
@@ -346,14 +1382,30 @@ impl Expression {
     }
 
     pub fn evaluate(self, ctx: SharedContext) -> Result<Expression, Box<dyn std::error::Error>> {
+        ctx.borrow_mut().budget.enter_evaluation()?;
+        let result = self.evaluate_nested(ctx.clone());
+        ctx.borrow_mut().budget.exit_evaluation();
+
+        result
+    }
+
+    fn evaluate_nested(self, ctx: SharedContext) -> Result<Expression, Box<dyn std::error::Error>> {
+        ctx.borrow_mut().budget.take_step()?;
+
         let result = match &self {
             Self::BooleanValue(_) => self,
             Self::IntegerValue(_) => self,
             Self::StringValue(_) => self,
             Self::Null => self,
             Self::EndOfProgram => self,
-            Self::Fn(_, _) => self,
+            Self::Fn(_, _, _, _) => {
+                let scope = ctx.borrow().scope.clone();
+                self.with_scope(&scope)
+            },
             Self::BuiltInFn(_, _) => self,
+            Self::PartialApplication(_, _, _) => self,
+            Self::Variant(_, _) => self,
+            Self::Map(_) => self,
 
             Self::Identifier(identifier) => {
                 self.evaluate_identifier(ctx.clone(), identifier.clone())?
@@ -364,15 +1416,60 @@ impl Expression {
             Self::List(expressions) => {
                 self.evaluate_list(ctx.clone(), expressions)?
             },
+            Self::MapLiteral(entries) => {
+                self.evaluate_map(ctx.clone(), entries)?
+            },
+            Self::Record(fields) => {
+                self.evaluate_record(ctx.clone(), fields)?
+            },
+            Self::RecordUpdate(record, fields) => {
+                self.evaluate_record_update(ctx.clone(), record, fields)?
+            },
+            Self::FieldAccess(record, field_name) => {
+                self.evaluate_field_access(ctx.clone(), record, field_name)?
+            },
+            Self::TypeDeclaration(_, constructors) => {
+                self.evaluate_type_declaration(ctx.clone(), constructors)?
+            },
+            Self::Match(value, arms) => {
+                self.evaluate_match(ctx.clone(), value, arms)?
+            },
+            Self::Propagate(value) => {
+                self.evaluate_propagate(ctx.clone(), value)?
+            },
+            Self::Throw(value) => {
+                self.evaluate_throw(ctx.clone(), value)?
+            },
+            Self::TryCatch(try_block, error_name, catch_block, finally_block) => {
+                self.evaluate_try_catch(ctx.clone(), try_block, error_name, catch_block, finally_block)?
+            },
             Self::LetBinding(variable_name, value) => {
                 self.evaluate_let_binding(ctx.clone(), variable_name, value)?
             },
-            Self::FnCall(function_name, argument_value) => {
-                self.evaluate_fn_call(ctx.clone(), function_name.clone(), argument_value)?
+            Self::FnDeclaration(fn_name, function) => {
+                self.evaluate_let_binding(ctx.clone(), fn_name, function)?
+            },
+            Self::Import(path, namespace_name) => {
+                self.evaluate_import(ctx.clone(), path, namespace_name)?
+            },
+            Self::FnCall(function, arguments, named_arguments) => {
+                self.evaluate_fn_call(ctx.clone(), function, arguments, named_arguments)?
             },
             Self::While(condition, body) => {
                 self.evaluate_while(ctx.clone(), condition, body)?
             },
+            Self::And(operands) => {
+                self.evaluate_short_circuit(ctx.clone(), operands, false)?
+            },
+            Self::Or(operands) => {
+                self.evaluate_short_circuit(ctx.clone(), operands, true)?
+            },
+            Self::Index(value, index) => {
+                self.evaluate_index(ctx.clone(), value, index)?
+            },
+            Self::Slice(value, start, end) => {
+                self.evaluate_slice(ctx.clone(), value, start, end)?
+            },
 
             Self::IfExpression(condition, if_block, else_block) => {
                 if condition.clone().evaluate(ctx.clone())?.boolean_value()? {
@@ -383,6 +1480,8 @@ impl Expression {
             },
             
             Self::Program(expressions) => {
+                Self::hoist_fn_declarations(ctx.clone(), expressions);
+
                 for expression in expressions.iter() {
                     expression.clone().evaluate(ctx.clone())?;
                 }
@@ -391,6 +1490,8 @@ impl Expression {
             },
         };
 
+        ctx.borrow().budget.check_value_size(&result)?;
+
         Ok(result)
     }
 }
\ No newline at end of file
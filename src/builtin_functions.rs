@@ -1,14 +1,28 @@
-use crate::ast::Expression;
+use crate::ast::{Expression, MapKey, SharedContext};
+use crate::capabilities::Capability;
+use crate::error::{ErrorKind, Exit, RuntimeError};
 
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::rc::Rc;
 use std::collections::HashMap;
 
+//.. Library of functions written in inflang itself, evaluated on top of the
+//   built-in functions when starting (see `EvaluationContext::load_prelude`).
+pub const PRELUDE: &str = include_str!("prelude.inf");
+
 pub fn builtin_functions() -> HashMap<String, Expression> {
     let mut function_map = HashMap::new();
 
     function_map.insert(
         "print".to_string(),
-        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            ctx.borrow().capabilities.check(Capability::Console, "print")?;
+
             match items[0].clone() {
                 Expression::StringValue(string) => print!("{}", string),
                 other => print!("{}", other),
@@ -20,7 +34,9 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
 
     function_map.insert(
         "print_line".to_string(),
-        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            ctx.borrow().capabilities.check(Capability::Console, "print_line")?;
+
             match items[0].clone() {
                 Expression::StringValue(string) => println!("{}", string),
                 other => println!("{}", other),
@@ -30,13 +46,74 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
         }
     )));
 
+    function_map.insert(
+        "eprint".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            ctx.borrow().capabilities.check(Capability::Console, "eprint")?;
+
+            match items[0].clone() {
+                Expression::StringValue(string) => eprint!("{}", string),
+                other => eprint!("{}", other),
+            };
+
+            Ok(Expression::Null)
+        }
+    )));
+
+    function_map.insert(
+        "eprint_line".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            ctx.borrow().capabilities.check(Capability::Console, "eprint_line")?;
+
+            match items[0].clone() {
+                Expression::StringValue(string) => eprintln!("{}", string),
+                other => eprintln!("{}", other),
+            };
+
+            Ok(Expression::Null)
+        }
+    )));
+
+    //.. `Some` of the value of an environment variable, or `None` if it isn't
+    //   set (or isn't valid Unicode).
+    function_map.insert(
+        "env_var".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            ctx.borrow().capabilities.check(Capability::Env, "env_var")?;
+
+            let name = items[0].clone().string_value()?;
+
+            Ok(match std::env::var(&name) {
+                Ok(value) => Expression::Variant("Some".to_string(), vec![Expression::StringValue(value)]),
+                Err(_) => Expression::Variant("None".to_string(), Vec::new()),
+            })
+        }
+    )));
+
+    //.. Ends the program with the given exit code. This can't be caught using
+    //   `try`, but `finally` blocks are still evaluated.
+    function_map.insert(
+        "exit".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            ctx.borrow().capabilities.check(Capability::Exit, "exit")?;
+
+            let code = items[0].clone().integer_value()?;
+
+            let code = i32::try_from(code).map_err(|_| {
+                RuntimeError::new(ErrorKind::Value, format!("{} is not a valid exit code", code))
+            })?;
+
+            Err(Exit(code).into())
+        }
+    )));
+
     function_map.insert(
         "+".to_string(),
         Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
             let a = items[0].clone().integer_value()?;
             let b = items[1].clone().integer_value()?;
 
-            Ok(Expression::IntegerValue(a + b))
+            checked_integer("+", &[a, b], a.checked_add(b))
         }
     )));
 
@@ -46,7 +123,11 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
             let a = items[0].clone().integer_value()?;
             let b = items[1].clone().integer_value()?;
 
-            Ok(Expression::IntegerValue(a / b))
+            if b == 0 {
+                return Err(RuntimeError::new(ErrorKind::Value, "division by zero".to_string()).into());
+            }
+
+            checked_integer("/", &[a, b], a.checked_div(b))
         }
     )));
 
@@ -56,7 +137,7 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
             let a = items[0].clone().integer_value()?;
             let b = items[1].clone().integer_value()?;
 
-            Ok(Expression::IntegerValue(a - b))
+            checked_integer("-", &[a, b], a.checked_sub(b))
         }
     )));
 
@@ -66,27 +147,56 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
             let a = items[0].clone().integer_value()?;
             let b = items[1].clone().integer_value()?;
 
-            Ok(Expression::IntegerValue(a * b))
+            checked_integer("*", &[a, b], a.checked_mul(b))
         }
     )));
 
+    //.. `<` and `>` compare integers, but also strings (by their characters'
+    //   code points), booleans and lists (see `compare_values`).
     function_map.insert(
         "<".to_string(),
         Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
-            let a = items[0].clone().integer_value()?;
-            let b = items[1].clone().integer_value()?;
+            let ordering = compare_values(&items[0], &items[1])?;
 
-            Ok(Expression::BooleanValue(a < b))
+            Ok(Expression::BooleanValue(ordering == Ordering::Less))
         }
     )));
 
     function_map.insert(
         ">".to_string(),
         Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
-            let a = items[0].clone().integer_value()?;
-            let b = items[1].clone().integer_value()?;
+            let ordering = compare_values(&items[0], &items[1])?;
+
+            Ok(Expression::BooleanValue(ordering == Ordering::Greater))
+        }
+    )));
+
+    function_map.insert(
+        "<=".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let ordering = compare_values(&items[0], &items[1])?;
 
-            Ok(Expression::BooleanValue(a > b))
+            Ok(Expression::BooleanValue(ordering != Ordering::Greater))
+        }
+    )));
+
+    function_map.insert(
+        ">=".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let ordering = compare_values(&items[0], &items[1])?;
+
+            Ok(Expression::BooleanValue(ordering != Ordering::Less))
+        }
+    )));
+
+    //.. Returns -1, 0 or 1 when the first value is respectively less than,
+    //   equal to or greater than the second value.
+    function_map.insert(
+        "compare".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let ordering = compare_values(&items[0], &items[1])?;
+
+            Ok(Expression::IntegerValue(ordering as i64))
         }
     )));
 
@@ -97,6 +207,13 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
         }
     )));
 
+    function_map.insert(
+        "!=".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            Ok(Expression::BooleanValue(items[0] != items[1]))
+        }
+    )));
+
     function_map.insert(
         "not".to_string(),
         Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
@@ -111,7 +228,11 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
             let a = items[0].clone().integer_value()?;
             let b = items[1].clone().integer_value()?;
 
-            Ok(Expression::IntegerValue(a % b))
+            if b == 0 {
+                return Err(RuntimeError::new(ErrorKind::Value, "division by zero".to_string()).into());
+            }
+
+            checked_integer("mod", &[a, b], a.checked_rem(b))
         }
     )));
 
@@ -120,13 +241,262 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
         Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
             let a = items[0].clone().integer_value()?;
 
-            Ok(Expression::IntegerValue((a as f64).sqrt() as u64))
+            if a < 0 {
+                return Err(RuntimeError::new(
+                    ErrorKind::Value, format!("can't take the square root of {}", a)
+                ).into());
+            }
+
+            //.. The floating point square root may be off by one for large
+            //   integers, so it's corrected to the floor of the exact root.
+            let mut root = (a as f64).sqrt() as i64;
+
+            while root.checked_mul(root).is_none_or(|square| square > a) {
+                root -= 1;
+            }
+
+            while (root + 1).checked_mul(root + 1).is_some_and(|square| square <= a) {
+                root += 1;
+            }
+
+            Ok(Expression::IntegerValue(root))
+        }
+    )));
+
+    //.. pow(base, exponent)
+    function_map.insert(
+        "pow".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let base = items[0].clone().integer_value()?;
+            let exponent = items[1].clone().integer_value()?;
+
+            if exponent < 0 {
+                return Err(RuntimeError::new(
+                    ErrorKind::Value, format!("can't raise to the negative power {}", exponent)
+                ).into());
+            }
+
+            checked_integer("pow", &[base, exponent], checked_pow(base, exponent))
+        }
+    )));
+
+    function_map.insert(
+        "abs".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let a = items[0].clone().integer_value()?;
+
+            checked_integer("abs", &[a], a.checked_abs())
+        }
+    )));
+
+    //.. Returns -1, 0 or 1 for negative integers, zero and positive integers.
+    function_map.insert(
+        "sign".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let a = items[0].clone().integer_value()?;
+
+            Ok(Expression::IntegerValue(a.signum()))
+        }
+    )));
+
+    //.. `min` and `max` work on any values `<` and `>` work on.
+    function_map.insert(
+        "min".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let ordering = compare_values(&items[0], &items[1])?;
+
+            Ok(if ordering == Ordering::Greater { items[1].clone() } else { items[0].clone() })
+        }
+    )));
+
+    function_map.insert(
+        "max".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let ordering = compare_values(&items[0], &items[1])?;
+
+            Ok(if ordering == Ordering::Less { items[1].clone() } else { items[0].clone() })
+        }
+    )));
+
+    //.. clamp(low, high, n): `n`, limited to the range from `low` to `high`
+    function_map.insert(
+        "clamp".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|_ctx, items| {
+            let low = items[0].clone().integer_value()?;
+            let high = items[1].clone().integer_value()?;
+            let n = items[2].clone().integer_value()?;
+
+            if low > high {
+                return Err(RuntimeError::new(
+                    ErrorKind::Value, format!("can't clamp between {} and {}", low, high)
+                ).into());
+            }
+
+            Ok(Expression::IntegerValue(n.clamp(low, high)))
+        }
+    )));
+
+    //.. The greatest common divisor, which is never negative.
+    function_map.insert(
+        "gcd".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let a = items[0].clone().integer_value()?;
+            let b = items[1].clone().integer_value()?;
+
+            checked_integer("gcd", &[a, b], i64::try_from(gcd(a, b)).ok())
+        }
+    )));
+
+    //.. The least common multiple, which is never negative.
+    function_map.insert(
+        "lcm".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let a = items[0].clone().integer_value()?;
+            let b = items[1].clone().integer_value()?;
+
+            let lcm = if a == 0 || b == 0 {
+                Some(0)
+            } else {
+                (a.unsigned_abs() / gcd(a, b))
+                    .checked_mul(b.unsigned_abs())
+                    .and_then(|lcm| i64::try_from(lcm).ok())
+            };
+
+            checked_integer("lcm", &[a, b], lcm)
+        }
+    )));
+
+    //.. ilog(base, n): the integer logarithm, rounded down, e.g.:
+    //   "ilog(10, 999)" is 2
+    function_map.insert(
+        "ilog".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let base = items[0].clone().integer_value()?;
+            let n = items[1].clone().integer_value()?;
+
+            if base < 2 || n < 1 {
+                return Err(RuntimeError::new(ErrorKind::Value, format!(
+                    "the logarithm of {} with base {} is undefined", n, base
+                )).into());
+            }
+
+            Ok(Expression::IntegerValue(n.ilog(base) as i64))
+        }
+    )));
+
+    //.. modpow(base, exponent, modulus): `base` to the power of `exponent`,
+    //   modulo `modulus`, without the intermediate results overflowing. The
+    //   result is never negative.
+    function_map.insert(
+        "modpow".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|_ctx, items| {
+            let base = items[0].clone().integer_value()?;
+            let mut exponent = items[1].clone().integer_value()?;
+            let modulus = items[2].clone().integer_value()?;
+
+            if exponent < 0 || modulus < 1 {
+                return Err(RuntimeError::new(ErrorKind::Value, format!(
+                    "`modpow` needs a non-negative exponent and a positive modulus, got {} and {}",
+                    exponent, modulus
+                )).into());
+            }
+
+            let modulus = modulus as i128;
+            let mut base = (base as i128).rem_euclid(modulus);
+            let mut result = 1 % modulus;
+
+            while exponent > 0 {
+                if exponent & 1 == 1 {
+                    result = result * base % modulus;
+                }
+
+                base = base * base % modulus;
+                exponent >>= 1;
+            }
+
+            Ok(Expression::IntegerValue(result as i64))
+        }
+    )));
+
+    //.. Random numbers, from the generator in the evaluation context. Its seed
+    //   is based on the time, unless it's set using `--seed` or `seed_random`.
+    function_map.insert(
+        "seed_random".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let seed = items[0].clone().integer_value()?;
+            ctx.borrow_mut().random.seed(seed);
+
+            Ok(Expression::Null)
+        }
+    )));
+
+    //.. random_int(low, high): an integer from `low` up to and including `high`
+    function_map.insert(
+        "random_int".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let low = items[0].clone().integer_value()?;
+            let high = items[1].clone().integer_value()?;
+
+            if low > high {
+                return Err(RuntimeError::new(ErrorKind::Value, format!(
+                    "can't pick a random integer between {} and {}", low, high
+                )).into());
+            }
+
+            Ok(Expression::IntegerValue(ctx.borrow_mut().random.next_in_range(low, high)))
+        }
+    )));
+
+    function_map.insert(
+        "random_choice".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let list = items[0].clone().list_value()?;
+
+            if list.is_empty() {
+                return Err(RuntimeError::new(
+                    ErrorKind::Value, "can't pick a random item from an empty list".to_string()
+                ).into());
+            }
+
+            let index = ctx.borrow_mut().random.next_below(list.len() as u64);
+
+            Ok(list[index as usize].clone())
+        }
+    )));
+
+    //.. The items of a list in a random order.
+    function_map.insert(
+        "shuffle".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let mut list = items[0].clone().list_value()?;
+
+            //.. Fisher-Yates shuffle
+            for i in (1..list.len()).rev() {
+                let j = ctx.borrow_mut().random.next_below(i as u64 + 1);
+                list.swap(i, j as usize);
+            }
+
+            Ok(Expression::List(list))
+        }
+    )));
+
+    //.. The number of milliseconds since the Unix epoch.
+    function_map.insert(
+        "clock".to_string(),
+        Expression::BuiltInFn(0, Rc::new(|ctx, _items| {
+            ctx.borrow().capabilities.check(Capability::Clock, "clock")?;
+
+            let milliseconds = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+
+            Ok(Expression::IntegerValue(milliseconds as i64))
         }
     )));
 
     function_map.insert(
         "get_input_line".to_string(),
-        Expression::BuiltInFn(0, Rc::new(|_ctx, _items| {
+        Expression::BuiltInFn(0, Rc::new(|ctx, _items| {
+            ctx.borrow().capabilities.check(Capability::Stdin, "get_input_line")?;
+
             let mut input = String::new();
             std::io::stdin().read_line(&mut input)?;
 
@@ -141,6 +511,128 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
         }
     )));
 
+    //.. File system functions. Paths are relative to the current directory,
+    //   and failures are raised as `io` errors mentioning the path. Which
+    //   paths may be accessed can be restricted by capabilities (see
+    //   `--allow-fs-read` and `--allow-fs-write`).
+    function_map.insert(
+        "read_file".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let path = items[0].clone().string_value()?;
+            ctx.borrow().capabilities.check_path(Capability::FsRead, "read_file", Path::new(&path))?;
+
+            let contents = fs::read_to_string(&path).map_err(|error| io_error("read", &path, error))?;
+
+            Ok(Expression::StringValue(contents))
+        }
+    )));
+
+    //.. The lines of a file, without their line endings.
+    function_map.insert(
+        "read_lines".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let path = items[0].clone().string_value()?;
+            ctx.borrow().capabilities.check_path(Capability::FsRead, "read_lines", Path::new(&path))?;
+
+            let contents = fs::read_to_string(&path).map_err(|error| io_error("read", &path, error))?;
+
+            Ok(Expression::List(
+                contents.lines().map(|line| Expression::StringValue(line.to_string())).collect()
+            ))
+        }
+    )));
+
+    //.. write_file(path, contents): creates or overwrites the file
+    function_map.insert(
+        "write_file".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let path = items[0].clone().string_value()?;
+            ctx.borrow().capabilities.check_path(Capability::FsWrite, "write_file", Path::new(&path))?;
+
+            let contents = items[1].clone().string_value()?;
+
+            fs::write(&path, contents).map_err(|error| io_error("write", &path, error))?;
+
+            Ok(Expression::Null)
+        }
+    )));
+
+    //.. append_file(path, contents): creates the file if it doesn't exist
+    function_map.insert(
+        "append_file".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let path = items[0].clone().string_value()?;
+            ctx.borrow().capabilities.check_path(Capability::FsWrite, "append_file", Path::new(&path))?;
+
+            let contents = items[1].clone().string_value()?;
+
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(contents.as_bytes()))
+                .map_err(|error| io_error("append to", &path, error))?;
+
+            Ok(Expression::Null)
+        }
+    )));
+
+    function_map.insert(
+        "file_exists".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let path = items[0].clone().string_value()?;
+            ctx.borrow().capabilities.check_path(Capability::FsRead, "file_exists", Path::new(&path))?;
+
+            Ok(Expression::BooleanValue(Path::new(&path).exists()))
+        }
+    )));
+
+    //.. The names of the entries of a directory, sorted.
+    function_map.insert(
+        "list_dir".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let path = items[0].clone().string_value()?;
+            ctx.borrow().capabilities.check_path(Capability::FsRead, "list_dir", Path::new(&path))?;
+
+            let mut names = fs::read_dir(&path)
+                .and_then(|entries| {
+                    entries
+                        .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+                        .collect::<Result<Vec<String>, std::io::Error>>()
+                })
+                .map_err(|error| io_error("list", &path, error))?;
+
+            names.sort();
+
+            Ok(Expression::List(names.into_iter().map(Expression::StringValue).collect()))
+        }
+    )));
+
+    function_map.insert(
+        "remove_file".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let path = items[0].clone().string_value()?;
+            ctx.borrow().capabilities.check_path(Capability::FsWrite, "remove_file", Path::new(&path))?;
+
+            fs::remove_file(&path).map_err(|error| io_error("remove", &path, error))?;
+
+            Ok(Expression::Null)
+        }
+    )));
+
+    //.. Creates a directory, and any missing parent directories.
+    function_map.insert(
+        "make_dir".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|ctx, items| {
+            let path = items[0].clone().string_value()?;
+            ctx.borrow().capabilities.check_path(Capability::FsWrite, "make_dir", Path::new(&path))?;
+
+            fs::create_dir_all(&path).map_err(|error| io_error("create directory", &path, error))?;
+
+            Ok(Expression::Null)
+        }
+    )));
+
     function_map.insert(
         "join_str".to_string(),
         Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
@@ -160,28 +652,262 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
         }
     )));
 
+    //.. String functions. Strings are indexed by character (Unicode scalar
+    //   value) rather than by byte, like when indexing with "s[i]".
     function_map.insert(
-        "list_len".to_string(),
+        "str_len".to_string(),
         Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
-            let len = items[0].clone().list_value()?.len();
+            let len = items[0].clone().string_value()?.chars().count();
 
-            Ok(Expression::IntegerValue(len as u64))
+            Ok(Expression::IntegerValue(len as i64))
         }
     )));
 
+    //.. substring(start, end, s): the characters from `start` up to (but not
+    //   including) `end`, where negative indices count from the end.
     function_map.insert(
-        "list_nth".to_string(),
-        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
-            let nth = items[0].clone().integer_value()?;
-            let list = items[1].clone().list_value()?;
+        "substring".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|_ctx, items| {
+            let string = items[2].clone().string_value()?;
+            let length = string.chars().count();
 
-            if nth > (list.len() as u64 - 1) {
-                return Err(format!(
-                    "trying to access element #{} of a list only containing {} elements",
-                    nth, list.len()
+            let start = Expression::resolve_index(items[0].integer_value()?, length, true)?;
+            let end = Expression::resolve_index(items[1].integer_value()?, length, true)?;
+
+            if start > end {
+                return Err(RuntimeError::new(
+                    ErrorKind::Index, format!("substring start {} is past substring end {}", start, end)
                 ).into());
             }
 
+            Ok(Expression::StringValue(string.chars().skip(start).take(end - start).collect()))
+        }
+    )));
+
+    //.. split(separator, s), e.g.: "split(",", "a,b")" is "["a", "b"]"
+    function_map.insert(
+        "split".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let separator = items[0].clone().string_value()?;
+            let string = items[1].clone().string_value()?;
+
+            if separator.is_empty() {
+                return Err(RuntimeError::new(
+                    ErrorKind::Value, "can't split on an empty separator, use `chars` instead".to_string()
+                ).into());
+            }
+
+            Ok(Expression::List(
+                string
+                    .split(&separator[..])
+                    .map(|part| Expression::StringValue(part.to_string()))
+                    .collect()
+            ))
+        }
+    )));
+
+    function_map.insert(
+        "trim".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let string = items[0].clone().string_value()?;
+
+            Ok(Expression::StringValue(string.trim().to_string()))
+        }
+    )));
+
+    function_map.insert(
+        "to_upper".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let string = items[0].clone().string_value()?;
+
+            Ok(Expression::StringValue(string.to_uppercase()))
+        }
+    )));
+
+    function_map.insert(
+        "to_lower".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let string = items[0].clone().string_value()?;
+
+            Ok(Expression::StringValue(string.to_lowercase()))
+        }
+    )));
+
+    //.. contains(part, s)
+    function_map.insert(
+        "contains".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let part = items[0].clone().string_value()?;
+            let string = items[1].clone().string_value()?;
+
+            Ok(Expression::BooleanValue(string.contains(&part[..])))
+        }
+    )));
+
+    //.. starts_with(prefix, s)
+    function_map.insert(
+        "starts_with".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let prefix = items[0].clone().string_value()?;
+            let string = items[1].clone().string_value()?;
+
+            Ok(Expression::BooleanValue(string.starts_with(&prefix[..])))
+        }
+    )));
+
+    //.. ends_with(suffix, s)
+    function_map.insert(
+        "ends_with".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let suffix = items[0].clone().string_value()?;
+            let string = items[1].clone().string_value()?;
+
+            Ok(Expression::BooleanValue(string.ends_with(&suffix[..])))
+        }
+    )));
+
+    //.. replace(from, to, s): replaces every occurrence of `from` in `s`
+    function_map.insert(
+        "replace".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|_ctx, items| {
+            let from = items[0].clone().string_value()?;
+            let to = items[1].clone().string_value()?;
+            let string = items[2].clone().string_value()?;
+
+            if from.is_empty() {
+                return Err(RuntimeError::new(
+                    ErrorKind::Value, "can't replace an empty string".to_string()
+                ).into());
+            }
+
+            Ok(Expression::StringValue(string.replace(&from[..], &to)))
+        }
+    )));
+
+    //.. index_of(part, s): `Some` of the (character) index of the first
+    //   occurrence of `part` in `s`, or `None` if it doesn't occur in it.
+    function_map.insert(
+        "index_of".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let part = items[0].clone().string_value()?;
+            let string = items[1].clone().string_value()?;
+
+            Ok(match string.find(&part[..]) {
+                Some(byte_index) => Expression::Variant(
+                    "Some".to_string(),
+                    vec![Expression::IntegerValue(string[..byte_index].chars().count() as i64)],
+                ),
+                None => Expression::Variant("None".to_string(), Vec::new()),
+            })
+        }
+    )));
+
+    //.. repeat(n, s)
+    function_map.insert(
+        "repeat".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let n = items[0].clone().integer_value()?;
+            let string = items[1].clone().string_value()?;
+
+            if n < 0 {
+                return Err(RuntimeError::new(
+                    ErrorKind::Value, format!("can't repeat a string {} times", n)
+                ).into());
+            }
+
+            let description = || format!("a string repeated {} times", n);
+            let length = string.len().checked_mul(n as usize).ok_or_else(|| too_large_error(description()))?;
+
+            ctx.borrow().budget.check_size(length)?;
+
+            let mut result = String::new();
+            result.try_reserve_exact(length).map_err(|_| too_large_error(description()))?;
+
+            if !string.is_empty() {
+                for _ in 0..n {
+                    result.push_str(&string);
+                }
+            }
+
+            Ok(Expression::StringValue(result))
+        }
+    )));
+
+    //.. The characters of a string, as a list of strings.
+    function_map.insert(
+        "chars".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let string = items[0].clone().string_value()?;
+
+            Ok(Expression::List(
+                string.chars().map(|ch| Expression::StringValue(ch.to_string())).collect()
+            ))
+        }
+    )));
+
+    //.. The code point of a string containing a single character.
+    function_map.insert(
+        "char_code".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let string = items[0].clone().string_value()?;
+            let mut chars = string.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) => Ok(Expression::IntegerValue(ch as i64)),
+                _ => Err(RuntimeError::new(ErrorKind::Value, format!(
+                    "expected a single character, got `{}`", items[0]
+                )).into()),
+            }
+        }
+    )));
+
+    //.. The string containing the character with the given code point.
+    function_map.insert(
+        "from_char_code".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let code = items[0].clone().integer_value()?;
+
+            let ch = u32::try_from(code).ok().and_then(char::from_u32).ok_or_else(|| {
+                RuntimeError::new(ErrorKind::Value, format!("{} is not a valid character code", code))
+            })?;
+
+            Ok(Expression::StringValue(ch.to_string()))
+        }
+    )));
+
+    //.. Converts any value to a string, the way `print` would print it.
+    function_map.insert(
+        "to_string".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            Ok(Expression::StringValue(match items[0].clone() {
+                Expression::StringValue(string) => string,
+                other => other.to_string(),
+            }))
+        }
+    )));
+
+    function_map.insert(
+        "list_len".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let len = items[0].clone().list_value()?.len();
+
+            Ok(Expression::IntegerValue(len as i64))
+        }
+    )));
+
+    function_map.insert(
+        "list_nth".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let nth = items[0].clone().integer_value()?;
+            let list = items[1].clone().list_value()?;
+
+            if nth < 0 || nth >= list.len() as i64 {
+                return Err(RuntimeError::new(ErrorKind::Index, format!(
+                    "trying to access element #{} of a list only containing {} elements",
+                    nth, list.len()
+                )).into());
+            }
+
             Ok(list[nth as usize].clone())
         }
     )));
@@ -198,5 +924,698 @@ pub fn builtin_functions() -> HashMap<String, Expression> {
         }
     )));
 
+    //.. list_concat(a, b): the items of `a` followed by the items of `b`
+    function_map.insert(
+        "list_concat".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let mut list = items[0].clone().list_value()?;
+            list.extend(items[1].clone().list_value()?);
+
+            Ok(Expression::List(list))
+        }
+    )));
+
+    //.. list_slice(start, end, list): the items from `start` up to (but not
+    //   including) `end`, where negative indices count from the end.
+    function_map.insert(
+        "list_slice".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|_ctx, items| {
+            let list = items[2].clone().list_value()?;
+
+            let start = Expression::resolve_index(items[0].integer_value()?, list.len(), true)?;
+            let end = Expression::resolve_index(items[1].integer_value()?, list.len(), true)?;
+
+            if start > end {
+                return Err(RuntimeError::new(
+                    ErrorKind::Index, format!("slice start {} is past slice end {}", start, end)
+                ).into());
+            }
+
+            Ok(Expression::List(list[start..end].to_vec()))
+        }
+    )));
+
+    function_map.insert(
+        "list_reverse".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let mut list = items[0].clone().list_value()?;
+            list.reverse();
+
+            Ok(Expression::List(list))
+        }
+    )));
+
+    //.. Sorts a list of integers or strings (see `compare_values`). Equal
+    //   items keep their order.
+    function_map.insert(
+        "list_sort".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let mut list = items[0].clone().list_value()?;
+            sort_values(&mut list, |item| item)?;
+
+            Ok(Expression::List(list))
+        }
+    )));
+
+    //.. list_insert(index, item, list): inserts `item` before the item at
+    //   `index`, where an index equal to the length of the list appends it.
+    function_map.insert(
+        "list_insert".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|_ctx, items| {
+            let mut list = items[2].clone().list_value()?;
+            let position = Expression::resolve_index(items[0].integer_value()?, list.len(), true)?;
+
+            list.insert(position, items[1].clone());
+
+            Ok(Expression::List(list))
+        }
+    )));
+
+    //.. list_remove(index, list)
+    function_map.insert(
+        "list_remove".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let mut list = items[1].clone().list_value()?;
+            let position = Expression::resolve_index(items[0].integer_value()?, list.len(), false)?;
+
+            list.remove(position);
+
+            Ok(Expression::List(list))
+        }
+    )));
+
+    //.. list_set(index, item, list): replaces the item at `index`
+    function_map.insert(
+        "list_set".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|_ctx, items| {
+            let mut list = items[2].clone().list_value()?;
+            let position = Expression::resolve_index(items[0].integer_value()?, list.len(), false)?;
+
+            list[position] = items[1].clone();
+
+            Ok(Expression::List(list))
+        }
+    )));
+
+    //.. list_contains(item, list)
+    function_map.insert(
+        "list_contains".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let list = items[1].clone().list_value()?;
+
+            Ok(Expression::BooleanValue(list.contains(&items[0])))
+        }
+    )));
+
+    //.. list_index_of(item, list): `Some` of the index of the first item equal
+    //   to `item`, or `None` if there is none.
+    function_map.insert(
+        "list_index_of".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let list = items[1].clone().list_value()?;
+
+            Ok(match list.iter().position(|item| *item == items[0]) {
+                Some(index) => Expression::Variant(
+                    "Some".to_string(), vec![Expression::IntegerValue(index as i64)]
+                ),
+                None => Expression::Variant("None".to_string(), Vec::new()),
+            })
+        }
+    )));
+
+    //.. `Some` of the first item of a list, or `None` if it's empty.
+    function_map.insert(
+        "list_first".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            Ok(match items[0].clone().list_value()?.first() {
+                Some(item) => Expression::Variant("Some".to_string(), vec![item.clone()]),
+                None => Expression::Variant("None".to_string(), Vec::new()),
+            })
+        }
+    )));
+
+    //.. `Some` of the last item of a list, or `None` if it's empty.
+    function_map.insert(
+        "list_last".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            Ok(match items[0].clone().list_value()?.last() {
+                Some(item) => Expression::Variant("Some".to_string(), vec![item.clone()]),
+                None => Expression::Variant("None".to_string(), Vec::new()),
+            })
+        }
+    )));
+
+    //.. list_range(start, end): the integers from `start` up to (but not
+    //   including) `end`
+    function_map.insert(
+        "list_range".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let start = items[0].clone().integer_value()?;
+            let end = items[1].clone().integer_value()?;
+
+            let length = end.saturating_sub(start).max(0) as usize;
+
+            ctx.borrow().budget.check_size(length)?;
+
+            let mut result = Vec::new();
+            result.try_reserve_exact(length).map_err(|_| {
+                too_large_error(format!("a range of {} integers", length))
+            })?;
+
+            result.extend((start..end).map(Expression::IntegerValue));
+
+            Ok(Expression::List(result))
+        }
+    )));
+
+    //.. Concatenates a list of lists, e.g.: "list_flatten([[1], [2, 3]])" is
+    //   "[1, 2, 3]". Only flattens a single level.
+    function_map.insert(
+        "list_flatten".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let mut result = Vec::new();
+
+            for item in items[0].clone().list_value()? {
+                result.extend(item.list_value()?);
+            }
+
+            Ok(Expression::List(result))
+        }
+    )));
+
+    //.. The items of a list without duplicates, keeping the first occurrence.
+    function_map.insert(
+        "list_unique".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let mut result: Vec<Expression> = Vec::new();
+
+            for item in items[0].clone().list_value()? {
+                if !result.contains(&item) {
+                    result.push(item);
+                }
+            }
+
+            Ok(Expression::List(result))
+        }
+    )));
+
+    function_map.insert(
+        "map_len".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let len = items[0].clone().map_value()?.len();
+
+            Ok(Expression::IntegerValue(len as i64))
+        }
+    )));
+
+    function_map.insert(
+        "map_get".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let key = items[0].clone();
+            let map = items[1].clone().map_value()?;
+
+            match map.get(&MapKey::try_from(&key)?) {
+                Some(value) => Ok(value.clone()),
+                None => Err(RuntimeError::new(
+                    ErrorKind::Key, format!("key `{}` does not exist in map", key)
+                ).into()),
+            }
+        }
+    )));
+
+    function_map.insert(
+        "map_insert".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|_ctx, items| {
+            let key = MapKey::try_from(&items[0])?;
+            let value = items[1].clone();
+            let mut map = items[2].clone().map_value()?;
+
+            map.insert(key, value);
+
+            Ok(Expression::Map(map))
+        }
+    )));
+
+    function_map.insert(
+        "map_remove".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let key = MapKey::try_from(&items[0])?;
+            let mut map = items[1].clone().map_value()?;
+
+            map.remove(&key);
+
+            Ok(Expression::Map(map))
+        }
+    )));
+
+    function_map.insert(
+        "map_contains".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let key = MapKey::try_from(&items[0])?;
+            let map = items[1].clone().map_value()?;
+
+            Ok(Expression::BooleanValue(map.contains_key(&key)))
+        }
+    )));
+
+    function_map.insert(
+        "map_keys".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let map = items[0].clone().map_value()?;
+
+            Ok(Expression::List(map.keys().map(MapKey::to_expression).collect()))
+        }
+    )));
+
+    function_map.insert(
+        "map_values".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let map = items[0].clone().map_value()?;
+
+            Ok(Expression::List(map.into_values().collect()))
+        }
+    )));
+
+    //.. Entries are returned as a list of `[key, value]` lists.
+    function_map.insert(
+        "map_entries".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let map = items[0].clone().map_value()?;
+
+            Ok(Expression::List(
+                map
+                    .into_iter()
+                    .map(|(key, value)| Expression::List(vec![key.to_expression(), value]))
+                    .collect()
+            ))
+        }
+    )));
+
+    //.. `Option` and `Result` values are variants, just like the ones created
+    //   by constructors of user-defined types, so they can be matched on.
+    function_map.insert(
+        "Some".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            Ok(Expression::Variant("Some".to_string(), items))
+        }
+    )));
+
+    function_map.insert(
+        "None".to_string(),
+        Expression::Variant("None".to_string(), Vec::new()),
+    );
+
+    function_map.insert(
+        "Ok".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            Ok(Expression::Variant("Ok".to_string(), items))
+        }
+    )));
+
+    function_map.insert(
+        "Err".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            Ok(Expression::Variant("Err".to_string(), items))
+        }
+    )));
+
+    function_map.insert(
+        "try_str_to_int".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let a = items[0].clone().string_value()?;
+
+            Ok(match a.parse() {
+                Ok(n) => Expression::Variant("Ok".to_string(), vec![Expression::IntegerValue(n)]),
+                Err(_) => Expression::Variant(
+                    "Err".to_string(),
+                    vec![Expression::StringValue(format!("`{}` is not an integer", a))],
+                ),
+            })
+        }
+    )));
+
+    function_map.insert(
+        "try_list_nth".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let nth = items[0].clone().integer_value()?;
+            let list = items[1].clone().list_value()?;
+
+            if nth < 0 || nth >= list.len() as i64 {
+                return Ok(Expression::Variant("None".to_string(), Vec::new()));
+            }
+
+            Ok(Expression::Variant("Some".to_string(), vec![list[nth as usize].clone()]))
+        }
+    )));
+
+    function_map.insert(
+        "try_map_get".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let key = MapKey::try_from(&items[0])?;
+            let map = items[1].clone().map_value()?;
+
+            Ok(match map.get(&key) {
+                Some(value) => Expression::Variant("Some".to_string(), vec![value.clone()]),
+                None => Expression::Variant("None".to_string(), Vec::new()),
+            })
+        }
+    )));
+
+    //.. Returns `None` once the end of the input has been reached.
+    function_map.insert(
+        "try_get_input_line".to_string(),
+        Expression::BuiltInFn(0, Rc::new(|ctx, _items| {
+            ctx.borrow().capabilities.check(Capability::Stdin, "try_get_input_line")?;
+
+            let mut input = String::new();
+
+            if std::io::stdin().read_line(&mut input)? == 0 {
+                return Ok(Expression::Variant("None".to_string(), Vec::new()));
+            }
+
+            if input.ends_with('\n') {
+                input.pop();
+                if input.ends_with('\r') {
+                    input.pop();
+                }
+            }
+
+            Ok(Expression::Variant("Some".to_string(), vec![Expression::StringValue(input)]))
+        }
+    )));
+
+    function_map.insert(
+        "arity".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let arity = items[0].arity()?;
+
+            Ok(Expression::IntegerValue(arity as i64))
+        }
+    )));
+
+    //.. Higher-order list functions. These call the given function through
+    //   the evaluator, so it can be any function, including built-in ones and
+    //   partial applications, and errors it raises are propagated as usual.
+    function_map.insert(
+        "map".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let list = items[1].clone().list_value()?;
+            let mut result = Vec::with_capacity(list.len());
+
+            for item in list {
+                result.push(call_argument(&ctx, "map", &items[0], vec![item])?);
+            }
+
+            Ok(Expression::List(result))
+        }
+    )));
+
+    function_map.insert(
+        "filter".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let mut result = Vec::new();
+
+            for item in items[1].clone().list_value()? {
+                if call_argument(&ctx, "filter", &items[0], vec![item.clone()])?.boolean_value()? {
+                    result.push(item);
+                }
+            }
+
+            Ok(Expression::List(result))
+        }
+    )));
+
+    //.. fold(f, initial, list): combines the items from left to right, e.g.:
+    //   "fold(+, 0, [1, 2, 3])" is "+(+(+(0, 1), 2), 3)"
+    function_map.insert(
+        "fold".to_string(),
+        Expression::BuiltInFn(3, Rc::new(|ctx, items| {
+            let mut result = items[1].clone();
+
+            for item in items[2].clone().list_value()? {
+                result = call_argument(&ctx, "fold", &items[0], vec![result, item])?;
+            }
+
+            Ok(result)
+        }
+    )));
+
+    //.. Like `fold`, but starting with the first item of the list.
+    function_map.insert(
+        "reduce".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let mut list = items[1].clone().list_value()?.into_iter();
+
+            let mut result = list.next().ok_or_else(|| {
+                RuntimeError::new(ErrorKind::Value, "`reduce` of an empty list".to_string())
+            })?;
+
+            for item in list {
+                result = call_argument(&ctx, "reduce", &items[0], vec![result, item])?;
+            }
+
+            Ok(result)
+        }
+    )));
+
+    function_map.insert(
+        "any".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            for item in items[1].clone().list_value()? {
+                if call_argument(&ctx, "any", &items[0], vec![item])?.boolean_value()? {
+                    return Ok(Expression::BooleanValue(true));
+                }
+            }
+
+            Ok(Expression::BooleanValue(false))
+        }
+    )));
+
+    function_map.insert(
+        "all".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            for item in items[1].clone().list_value()? {
+                if !call_argument(&ctx, "all", &items[0], vec![item])?.boolean_value()? {
+                    return Ok(Expression::BooleanValue(false));
+                }
+            }
+
+            Ok(Expression::BooleanValue(true))
+        }
+    )));
+
+    //.. Returns `Some` of the first item the function returns true for.
+    function_map.insert(
+        "find".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            for item in items[1].clone().list_value()? {
+                if call_argument(&ctx, "find", &items[0], vec![item.clone()])?.boolean_value()? {
+                    return Ok(Expression::Variant("Some".to_string(), vec![item]));
+                }
+            }
+
+            Ok(Expression::Variant("None".to_string(), Vec::new()))
+        }
+    )));
+
+    //.. Like `map`, but the function returns a list for every item, which are
+    //   concatenated.
+    function_map.insert(
+        "flat_map".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let mut result = Vec::new();
+
+            for item in items[1].clone().list_value()? {
+                result.extend(call_argument(&ctx, "flat_map", &items[0], vec![item])?.list_value()?);
+            }
+
+            Ok(Expression::List(result))
+        }
+    )));
+
+    //.. Pairs up the items of two lists, e.g.: "zip([1, 2], ["a", "b"])" is
+    //   "[[1, "a"], [2, "b"]]". The result is as long as the shortest list.
+    function_map.insert(
+        "zip".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|_ctx, items| {
+            let a = items[0].clone().list_value()?;
+            let b = items[1].clone().list_value()?;
+
+            Ok(Expression::List(
+                a.into_iter()
+                    .zip(b)
+                    .map(|(a, b)| Expression::List(vec![a, b]))
+                    .collect()
+            ))
+        }
+    )));
+
+    //.. Pairs up the items of a list with their index, e.g.:
+    //   "enumerate(["a", "b"])" is "[[0, "a"], [1, "b"]]"
+    function_map.insert(
+        "enumerate".to_string(),
+        Expression::BuiltInFn(1, Rc::new(|_ctx, items| {
+            let list = items[0].clone().list_value()?;
+
+            Ok(Expression::List(
+                list.into_iter()
+                    .enumerate()
+                    .map(|(index, item)| Expression::List(vec![Expression::IntegerValue(index as i64), item]))
+                    .collect()
+            ))
+        }
+    )));
+
+    //.. sort_by(key, list): sorts the list by the key the function returns
+    //   for each item. Items with equal keys keep their order.
+    function_map.insert(
+        "sort_by".to_string(),
+        Expression::BuiltInFn(2, Rc::new(|ctx, items| {
+            let mut keyed_items = Vec::new();
+
+            for item in items[1].clone().list_value()? {
+                let key = call_argument(&ctx, "sort_by", &items[0], vec![item.clone()])?;
+                keyed_items.push((key, item));
+            }
+
+            sort_values(&mut keyed_items, |(key, _)| key)?;
+
+            Ok(Expression::List(keyed_items.into_iter().map(|(_, item)| item).collect()))
+        }
+    )));
+
     function_map
 }
+
+fn io_error(action: &str, path: &str, error: std::io::Error) -> Box<dyn std::error::Error> {
+    RuntimeError::new(
+        ErrorKind::Io, format!("failed to {} `{}`: {}", action, path, error)
+    ).into()
+}
+
+//.. The result of integer arithmetic, which is `None` if it overflowed.
+fn checked_integer(operation: &str, operands: &[i64], result: Option<i64>) -> Result<Expression, Box<dyn std::error::Error>> {
+    result.map(Expression::IntegerValue).ok_or_else(|| {
+        let operands: Vec<String> = operands.iter().map(|operand| operand.to_string()).collect();
+
+        RuntimeError::new(
+            ErrorKind::Overflow, format!("integer overflow in `{}({})`", operation, operands.join(", "))
+        ).into()
+    })
+}
+
+//.. Raised when the memory for a value can't be allocated, instead of the
+//   interpreter being aborted.
+fn too_large_error(description: String) -> Box<dyn std::error::Error> {
+    RuntimeError::new(ErrorKind::Value, format!("{} is too large to be created", description)).into()
+}
+
+//.. Exponentiation by squaring, `None` if it overflows.
+fn checked_pow(mut base: i64, mut exponent: i64) -> Option<i64> {
+    let mut result: i64 = 1;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.checked_mul(base)?;
+        }
+
+        exponent >>= 1;
+
+        if exponent > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+
+    Some(result)
+}
+
+fn gcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+
+    while b != 0 {
+        let remainder = a % b;
+        a = b;
+        b = remainder;
+    }
+
+    a
+}
+
+//.. Calls a function that was given as an argument to a built-in function.
+fn call_argument(ctx: &SharedContext, builtin_name: &str, function: &Expression, arguments: Vec<Expression>) -> Result<Expression, Box<dyn std::error::Error>> {
+    Expression::call_function(
+        ctx.clone(),
+        &format!("<function given to {}>", builtin_name),
+        function.clone(),
+        arguments,
+        Vec::new(),
+    )
+}
+
+//.. Sorts items by the value `key` gives back for them, keeping the order of
+//   items with equal keys.
+fn sort_values<T>(items: &mut [T], key: impl Fn(&T) -> &Expression) -> Result<(), Box<dyn std::error::Error>> {
+    let mut comparison_error = None;
+
+    items.sort_by(|a, b| {
+        compare_values(key(a), key(b)).unwrap_or_else(|error| {
+            comparison_error.get_or_insert(error);
+            fallback_order(key(a), key(b))
+        })
+    });
+
+    match comparison_error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+//.. A total order of all values, which agrees with `compare_values` where
+//   that gives an order and orders values of different types by their type.
+//   Used once items turn out not to be comparable, as sorting with an order
+//   that isn't total panics; the sort still fails with the comparison error.
+fn fallback_order(a: &Expression, b: &Expression) -> Ordering {
+    let type_rank = |value: &Expression| match value {
+        Expression::Null => 0,
+        Expression::BooleanValue(_) => 1,
+        Expression::IntegerValue(_) => 2,
+        Expression::StringValue(_) => 3,
+        Expression::List(_) => 4,
+        _ => 5,
+    };
+
+    match (a, b) {
+        (Expression::List(a), Expression::List(b)) => {
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| fallback_order(a, b))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or_else(|| a.len().cmp(&b.len()))
+        },
+        (a, b) if type_rank(a) != type_rank(b) => type_rank(a).cmp(&type_rank(b)),
+        (a, b) => compare_values(a, b).unwrap_or(Ordering::Equal),
+    }
+}
+
+//.. Orders integers, strings and booleans, and lists of these (item by item).
+fn compare_values(a: &Expression, b: &Expression) -> Result<Ordering, Box<dyn std::error::Error>> {
+    match (a, b) {
+        (Expression::IntegerValue(a), Expression::IntegerValue(b)) => Ok(a.cmp(b)),
+        (Expression::StringValue(a), Expression::StringValue(b)) => Ok(a.cmp(b)),
+        (Expression::BooleanValue(a), Expression::BooleanValue(b)) => Ok(a.cmp(b)),
+        (Expression::List(a), Expression::List(b)) => {
+            for (a, b) in a.iter().zip(b) {
+                let ordering = compare_values(a, b)?;
+
+                if ordering != Ordering::Equal {
+                    return Ok(ordering);
+                }
+            }
+
+            Ok(a.len().cmp(&b.len()))
+        },
+        (a, b) => Err(RuntimeError::new(
+            ErrorKind::Type, format!("can't compare `{}` with `{}`", a, b)
+        ).into()),
+    }
+}
@@ -0,0 +1,177 @@
+use crate::error::{ErrorKind, RuntimeError};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+//.. Kinds of access to the outside world a program can be granted.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Capability {
+    Console,    //.. printing to stdout and stderr
+    Stdin,      //.. reading input
+    FsRead,     //.. reading files and directories, including imports
+    FsWrite,    //.. creating, changing and removing files and directories
+    Env,        //.. reading environment variables
+    Exit,       //.. ending the program with an exit code
+    Clock,      //.. reading the current time
+}
+
+impl Capability {
+    const ALL: [Capability; 7] = [
+        Capability::Console,
+        Capability::Stdin,
+        Capability::FsRead,
+        Capability::FsWrite,
+        Capability::Env,
+        Capability::Exit,
+        Capability::Clock,
+    ];
+
+    //.. The name used in `--allow-<name>` command line flags.
+    pub fn flag_name(&self) -> &'static str {
+        match *self {
+            Capability::Console => "console",
+            Capability::Stdin   => "stdin",
+            Capability::FsRead  => "fs-read",
+            Capability::FsWrite => "fs-write",
+            Capability::Env     => "env",
+            Capability::Exit    => "exit",
+            Capability::Clock   => "clock",
+        }
+    }
+
+    pub fn from_flag_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|capability| capability.flag_name() == name)
+    }
+}
+
+pub struct Capabilities {
+    //.. The granted capabilities, each with the directories it's restricted
+    //   to, where no directories means it isn't restricted. Only file system
+    //   capabilities can be restricted to directories.
+    granted: HashMap<Capability, Vec<PathBuf>>,
+}
+
+impl Capabilities {
+    pub fn all() -> Self {
+        Self {
+            granted: Capability::ALL.iter().map(|capability| (*capability, Vec::new())).collect(),
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            granted: HashMap::new(),
+        }
+    }
+
+    //.. Grants a capability, restricted to `directory` when given. Granting
+    //   it again for another directory allows both directories, granting it
+    //   without a directory lifts the restriction.
+    pub fn grant(&mut self, capability: Capability, directory: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let directory = match directory {
+            Some(directory) => {
+                if capability != Capability::FsRead && capability != Capability::FsWrite {
+                    return Err(format!(
+                        "`--allow-{}` can't be restricted to a directory", capability.flag_name()
+                    ).into());
+                }
+
+                Some(Path::new(directory).canonicalize().map_err(|error| {
+                    format!("can't allow access to `{}`: {}", directory, error)
+                })?)
+            },
+            None => None,
+        };
+
+        let was_unrestricted = self.granted.get(&capability).is_some_and(|directories| directories.is_empty());
+        let directories = self.granted.entry(capability).or_default();
+
+        match directory {
+            Some(directory) if !was_unrestricted => directories.push(directory),
+            _ => directories.clear(),
+        }
+
+        Ok(())
+    }
+
+    //.. Fails with a permission error if `capability` isn't granted, naming
+    //   the function that needs it.
+    pub fn check(&self, capability: Capability, function_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if self.granted.contains_key(&capability) {
+            return Ok(());
+        }
+
+        Err(Self::permission_error(format!(
+            "`{}` isn't allowed, as the program wasn't given `--allow-{}`",
+            function_name,
+            capability.flag_name(),
+        )))
+    }
+
+    //.. Like `check`, but also fails if the capability is restricted to
+    //   directories which don't contain `path`.
+    pub fn check_path(&self, capability: Capability, function_name: &str, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.check(capability, function_name)?;
+
+        let directories = &self.granted[&capability];
+
+        if directories.is_empty() {
+            return Ok(());
+        }
+
+        let is_allowed = Self::absolute_path(path)
+            .is_some_and(|path| directories.iter().any(|directory| path.starts_with(directory)));
+
+        if is_allowed {
+            return Ok(());
+        }
+
+        Err(Self::permission_error(format!(
+            "`{}` isn't allowed to access `{}`, as it's outside the directories given to `--allow-{}`",
+            function_name,
+            path.display(),
+            capability.flag_name(),
+        )))
+    }
+
+    //.. The absolute path of a (possibly not yet existing) file, with symbolic
+    //   links resolved. Paths which would need `..` to be resolved in a part
+    //   that doesn't exist give `None`, as they can't be resolved reliably.
+    //   So do paths with a part that can't be resolved but does exist, like
+    //   a symbolic link to a missing file, which writing to would create
+    //   wherever the link points.
+    fn absolute_path(path: &Path) -> Option<PathBuf> {
+        let mut existing_path = path;
+        let mut missing_components = Vec::new();
+
+        loop {
+            let parent_directory = if existing_path.as_os_str().is_empty() { Path::new(".") } else { existing_path };
+
+            if let Ok(canonical_path) = parent_directory.canonicalize() {
+                let mut absolute_path = canonical_path;
+
+                for component in missing_components.iter().rev() {
+                    match component {
+                        Component::Normal(name) => absolute_path.push(name),
+                        Component::CurDir => (),
+                        _ => return None,
+                    }
+                }
+
+                return Some(absolute_path);
+            }
+
+            if fs::symlink_metadata(parent_directory).is_ok() {
+                return None;
+            }
+
+            missing_components.push(existing_path.components().next_back()?);
+            existing_path = existing_path.parent()?;
+        }
+    }
+
+    fn permission_error(message: String) -> Box<dyn std::error::Error> {
+        RuntimeError::new(ErrorKind::Permission, message).into()
+    }
+}
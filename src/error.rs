@@ -0,0 +1,181 @@
+use crate::ast::Expression;
+
+use std::error::Error;
+
+//.. Raised by the `?` operator when it's applied to an `Err` or `None` value,
+//   and caught by the enclosing function call, which then returns the value.
+pub struct EarlyReturn(pub Expression);
+
+impl std::fmt::Display for EarlyReturn {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "`?` propagated `{}` outside of any function", self.0)
+    }
+}
+
+impl std::fmt::Debug for EarlyReturn {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Error for EarlyReturn {}
+
+//.. Raised by `throw`, containing the thrown value.
+pub struct Thrown(pub Expression);
+
+impl std::fmt::Display for Thrown {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "uncaught exception `{}`", self.0)
+    }
+}
+
+impl std::fmt::Debug for Thrown {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Error for Thrown {}
+
+//.. Raised by `exit`, containing the exit code. It propagates through
+//   function calls and `try`, ending the program.
+pub struct Exit(pub i32);
+
+impl std::fmt::Display for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "exit with code {}", self.0)
+    }
+}
+
+impl std::fmt::Debug for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Error for Exit {}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ErrorKind {
+    Arity,       //.. wrong number of arguments
+    Import,      //.. module not found or imported in a cycle
+    Index,       //.. index out of range
+    Interrupted, //.. evaluation interrupted by Ctrl-C
+    Io,          //.. failed reading or writing
+    Key,         //.. missing map key or record field
+    Limit,       //.. execution limit exceeded
+    Match,       //.. no match arm matched
+    Name,        //.. unknown identifier
+    Overflow,    //.. integer result out of range
+    Permission,  //.. capability not granted
+    Runtime,     //.. anything else
+    Type,        //.. value of the wrong type
+    Value,       //.. right type, but invalid value
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let kind_as_str = match *self {
+            ErrorKind::Arity       => "arity",
+            ErrorKind::Import      => "import",
+            ErrorKind::Index       => "index",
+            ErrorKind::Interrupted => "interrupted",
+            ErrorKind::Io          => "io",
+            ErrorKind::Key         => "key",
+            ErrorKind::Limit       => "limit",
+            ErrorKind::Match       => "match",
+            ErrorKind::Name        => "name",
+            ErrorKind::Overflow    => "overflow",
+            ErrorKind::Permission  => "permission",
+            ErrorKind::Runtime     => "runtime",
+            ErrorKind::Type        => "type",
+            ErrorKind::Value       => "value",
+        };
+
+        write!(f, "{}", kind_as_str)
+    }
+}
+
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    //.. Names of the functions the error propagated out of, innermost first.
+    pub call_stack: Vec<String>,
+}
+
+impl RuntimeError {
+    pub fn new(kind: ErrorKind, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            call_stack: Vec::new(),
+        }
+    }
+
+    //.. Converts any error raised during evaluation into a `RuntimeError`.
+    //   Signals like `EarlyReturn`, `Thrown` and `Exit` aren't converted, but
+    //   are given back as `Err` instead.
+    pub fn from_error(error: Box<dyn Error>) -> Result<Self, Box<dyn Error>> {
+        if error.is::<EarlyReturn>() || error.is::<Thrown>() || error.is::<Exit>() {
+            return Err(error);
+        }
+
+        let error = match error.downcast::<RuntimeError>() {
+            Ok(runtime_error) => return Ok(*runtime_error),
+            Err(error) => error,
+        };
+
+        let kind = if error.is::<std::io::Error>() {
+            ErrorKind::Io
+        } else if error.is::<std::num::ParseIntError>() {
+            ErrorKind::Value
+        } else {
+            ErrorKind::Runtime
+        };
+
+        Ok(Self::new(kind, error.to_string()))
+    }
+
+    //.. The name of the innermost function the error occurred in, as errors
+    //   don't know their position in the source.
+    pub fn function_name(&self) -> String {
+        self.call_stack
+            .first()
+            .cloned()
+            .unwrap_or_else(|| "<top level>".to_string())
+    }
+
+    //.. The functions the error propagated out of, one per line, innermost
+    //   first.
+    pub fn stack_trace(&self) -> String {
+        self.call_stack
+            .iter()
+            .map(|function_name| format!("  in `{}`", function_name))
+            .chain(std::iter::once("  at <top level>".to_string()))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    //.. The value bound by `catch`, a record describing the error.
+    pub fn to_expression(&self) -> Expression {
+        Expression::Record(vec![
+            ("kind".to_string(), Expression::StringValue(self.kind.to_string())),
+            ("message".to_string(), Expression::StringValue(self.message.clone())),
+            ("function".to_string(), Expression::StringValue(self.function_name())),
+        ])
+    }
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::fmt::Debug for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Error for RuntimeError {}
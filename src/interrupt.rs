@@ -0,0 +1,46 @@
+use crate::error::{ErrorKind, RuntimeError};
+
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+//.. Set when Ctrl-C is pressed, and cleared once evaluation is interrupted.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+const SIGINT: c_int = 2;
+
+extern "C" {
+    fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+}
+
+extern "C" fn handle_sigint(_signum: c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+
+    //.. Some platforms reset the handler once a signal has been delivered.
+    unsafe {
+        signal(SIGINT, handle_sigint);
+    }
+}
+
+//.. Makes Ctrl-C interrupt evaluation (see `check`), instead of killing the
+//   interpreter.
+pub fn install_handler() {
+    unsafe {
+        signal(SIGINT, handle_sigint);
+    }
+}
+
+//.. Forgets about Ctrl-C being pressed while no program was being evaluated,
+//   e.g. while waiting for input in the REPL.
+pub fn reset() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+//.. Fails with an `interrupted` error if Ctrl-C was pressed since the last
+//   check. Called regularly during evaluation, by loops and function calls.
+pub fn check() -> Result<(), Box<dyn std::error::Error>> {
+    if INTERRUPTED.swap(false, Ordering::SeqCst) {
+        return Err(RuntimeError::new(ErrorKind::Interrupted, "interrupted".to_string()).into());
+    }
+
+    Ok(())
+}
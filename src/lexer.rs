@@ -1,51 +1,88 @@
 use std::cmp::{max, min};
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
+    And,                //.. and
+    As,                 //.. as
     AssignmentOperator, //.. =
+    Catch,              //.. catch
+    Colon,              //.. :
     Comma,              //.. ,
+    Dot,                //.. .
+    DotDot,             //.. ..
     Else,               //.. else
     EOF,
     False,              //.. false
+    FatArrow,           //.. =>
+    Finally,            //.. finally
     Fn,                 //.. fn
+    HashLCurlyBrace,    //.. #{
     Identifier,         //.. x
     If,                 //.. if
+    Import,             //.. import
     Integer,            //.. 0-9
     LCurlyBrace,        //.. {
     Let,                //.. let
     LParen,             //.. (
     LSquareBracket,     //.. [
+    Match,              //.. match
+    Or,                 //.. or
+    Pipe,               //.. |
+    PipeOperator,       //.. |>
+    QuestionMark,       //.. ?
     RCurlyBrace,        //.. }
     RParen,             //.. )
     RSquareBracket,     //.. ]
     Semicolon,          //.. ;
     StringLiteral,      //.. "*"
+    Throw,              //.. throw
     True,               //.. true
+    Try,                //.. try
+    Type,               //.. type
     While,              //.. while
 }
 
 impl std::fmt::Display for TokenType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let token_type_as_str = match *self {
+            TokenType::And                  => "and",
+            TokenType::As                   => "as",
             TokenType::AssignmentOperator   => "=",
+            TokenType::Catch                => "catch",
+            TokenType::Colon                => ":",
             TokenType::Comma                => ",",
+            TokenType::Dot                  => ".",
+            TokenType::DotDot               => "..",
             TokenType::Else                 => "else",
             TokenType::EOF                  => "end of file",
             TokenType::False                => "false",
+            TokenType::FatArrow             => "=>",
+            TokenType::Finally              => "finally",
             TokenType::Fn                   => "fn",
+            TokenType::HashLCurlyBrace      => "#{",
             TokenType::Identifier           => "identifier",
             TokenType::If                   => "if",
+            TokenType::Import               => "import",
             TokenType::Integer              => "integer",
             TokenType::LCurlyBrace          => "{",
             TokenType::Let                  => "let",
             TokenType::LParen               => "(",
             TokenType::LSquareBracket       => "[",
+            TokenType::Match                => "match",
+            TokenType::Or                   => "or",
+            TokenType::Pipe                 => "|",
+            TokenType::PipeOperator         => "|>",
+            TokenType::QuestionMark         => "?",
             TokenType::RCurlyBrace          => "}",
             TokenType::RParen               => ")",
             TokenType::RSquareBracket       => "]",
             TokenType::Semicolon            => ";",
             TokenType::StringLiteral        => "string literal",
+            TokenType::Throw                => "throw",
             TokenType::True                 => "true",
+            TokenType::Try                  => "try",
+            TokenType::Type                 => "type",
             TokenType::While                => "while"
         };
 
@@ -93,7 +130,7 @@ pub struct Lexer {
 impl Lexer {
     pub fn new(source: String) -> Self {
         Self {
-            source: source,
+            source,
             current_index: 0,
         }
     }
@@ -157,46 +194,37 @@ impl Lexer {
         }
     }
 
-    #[allow(dead_code)]
-    fn expect(&mut self, expected_str: String) -> Result<(), Box<dyn std::error::Error>> {
-        let actual = self
-            .take(expected_str.len())
-            .into_iter()
-            .collect::<String>();
-
-        if actual == expected_str {
-            Ok(())
-        } else {
-            Err(format!("expected `{}`, got `{}` instead", expected_str, actual).into())
-        }
+    //.. characters which end an identifier, keyword or integer without being
+    //   part of it
+    fn is_delimiter(ch: char) -> bool {
+        ch.is_whitespace()
+            || ch == '('
+            || ch == ')'
+            || ch == ';'
+            || ch == ','
+            || ch == '['
+            || ch == ']'
+            || ch == '}'
+            || ch == '{'
+            || ch == '.'
+            || ch == ':'
+            || ch == '|'
+            || ch == '?'
     }
 
     //.. this function takes strings which could either be keywords or identifiers
     fn keyword_or_identifier(&mut self) -> Result<String, Box<dyn std::error::Error>> {
         let mut result = String::new();
 
-        loop {
-            let ch = match self.take_one() {
-                Some(ch) => ch,
-                None => break,
-            };
-
-            if ch.is_whitespace()
-                || ch == '('
-                || ch == ')'
-                || ch == ';'
-                || ch == ','
-                || ch == '['
-                || ch == ']'
-                || ch == '}'
-                || ch == '{'
-            {
+        while let Some(ch) = self.take_one() {
+            if Self::is_delimiter(ch) {
                 self.rewind(1);
                 break;
             }
 
             if !(ch == '+'
                 || ch == '='
+                || ch == '!'
                 || ch == '*'
                 || ch == '/'
                 || ch == '-'
@@ -229,6 +257,16 @@ impl Lexer {
             "false" => Token { token_type: TokenType::False, value: String::new() },
             "fn"    => Token { token_type: TokenType::Fn, value: String::new() },
             "while" => Token { token_type: TokenType::While, value: String::new() },
+            "type"  => Token { token_type: TokenType::Type, value: String::new() },
+            "match" => Token { token_type: TokenType::Match, value: String::new() },
+            "throw" => Token { token_type: TokenType::Throw, value: String::new() },
+            "try"   => Token { token_type: TokenType::Try, value: String::new() },
+            "catch" => Token { token_type: TokenType::Catch, value: String::new() },
+            "finally" => Token { token_type: TokenType::Finally, value: String::new() },
+            "and"   => Token { token_type: TokenType::And, value: String::new() },
+            "or"    => Token { token_type: TokenType::Or, value: String::new() },
+            "import" => Token { token_type: TokenType::Import, value: String::new() },
+            "as"    => Token { token_type: TokenType::As, value: String::new() },
             _       => Token { token_type: TokenType::Identifier, value: keyword_or_identifier }
         })
     }
@@ -236,27 +274,20 @@ impl Lexer {
     fn take_integer(&mut self) -> Result<Token, Box<dyn std::error::Error>> {
         let mut result = String::new();
 
-        loop {
-            let ch = match self.take_one() {
-                Some(ch) => ch,
-                None => break,
-            };
+        //.. negative integer literals, e.g. `-1`, which negative indices like
+        //   `xs[-1]` need (and why integers are signed)
+        if self.peek_one() == Some('-') {
+            self.consume(1);
+            result.push('-');
+        }
 
-            if ch.is_whitespace()
-                || ch == '('
-                || ch == ')'
-                || ch == ';'
-                || ch == ','
-                || ch == '['
-                || ch == ']'
-                || ch == '}'
-                || ch == '{'
-            {
+        while let Some(ch) = self.take_one() {
+            if Self::is_delimiter(ch) {
                 self.rewind(1);
                 break;
             }
 
-            if !ch.is_digit(10) {
+            if !ch.is_ascii_digit() {
                 return Err(format!(
                     "`{}`: unexpected `{}` while scanning integer",
                     result, ch
@@ -312,7 +343,7 @@ impl Lexer {
             Some('%')       => {
                 loop {
                     let next_char = self.take_one();
-                    if next_char == Some('\n') || next_char == None {
+                    if next_char == Some('\n') || next_char.is_none() {
                         break
                     }
                 }
@@ -321,12 +352,48 @@ impl Lexer {
             Some('(')       => consume_and_return(self, TokenType::LParen),
             Some(')')       => consume_and_return(self, TokenType::RParen),
             Some(';')       => consume_and_return(self, TokenType::Semicolon),
-            Some('=')       => consume_and_return(self, TokenType::AssignmentOperator),
+            Some('=')       => {
+                if self.peek(2) == vec!['=', '>'] {
+                    self.consume(2);
+                    Ok(Token { token_type: TokenType::FatArrow, value: String::new() })
+                } else {
+                    consume_and_return(self, TokenType::AssignmentOperator)
+                }
+            },
+            Some('|')       => {
+                if self.peek(2) == vec!['|', '>'] {
+                    self.consume(2);
+                    Ok(Token { token_type: TokenType::PipeOperator, value: String::new() })
+                } else {
+                    consume_and_return(self, TokenType::Pipe)
+                }
+            },
+            Some('?')       => consume_and_return(self, TokenType::QuestionMark),
             Some(',')       => consume_and_return(self, TokenType::Comma),
+            Some(':')       => consume_and_return(self, TokenType::Colon),
+            Some('#')       => {
+                if self.peek(2) == vec!['#', '{'] {
+                    self.consume(2);
+                    Ok(Token { token_type: TokenType::HashLCurlyBrace, value: String::new() })
+                } else {
+                    Err("unexpected `#`, did you mean `#{`?".into())
+                }
+            },
             Some('{')       => consume_and_return(self, TokenType::LCurlyBrace),
             Some('}')       => consume_and_return(self, TokenType::RCurlyBrace),
             Some('[')       => consume_and_return(self, TokenType::LSquareBracket),
             Some(']')       => consume_and_return(self, TokenType::RSquareBracket),
+            Some('.')       => {
+                if self.peek(2) == vec!['.', '.'] {
+                    self.consume(2);
+                    Ok(Token { token_type: TokenType::DotDot, value: String::new() })
+                } else {
+                    consume_and_return(self, TokenType::Dot)
+                }
+            },
+            Some('-') if self.peek(2).last().is_some_and(|ch| ch.is_ascii_digit()) => {
+                self.take_integer()
+            },
             Some('0'..='9') => self.take_integer(),
             Some('"')       => self.take_string_literal(),
             Some(_)         => self.take_keyword_or_identifier(),
@@ -0,0 +1,220 @@
+use crate::ast::{Expression, MapKey};
+use crate::error::{ErrorKind, RuntimeError};
+
+use std::time::{Duration, Instant};
+
+//.. The call depth allowed when no other maximum is given, which keeps
+//   runaway recursion from overflowing the stack of the interpreter. Looking
+//   up a variable doesn't go through the scopes of the callers, so reaching
+//   this depth takes time linear in it.
+pub const DEFAULT_MAX_DEPTH: usize = 10_000;
+
+//.. Expressions being evaluated inside each other are what uses the stack of
+//   the interpreter, and there are more of them than function calls: the body
+//   of a function can nest blocks, `match`es, `try`s and arguments before the
+//   next call. The call depth limit allows this many of them for each call,
+//   plus `MIN_NESTING` for expressions outside of any function.
+const NESTING_PER_CALL: usize = 10;
+const MIN_NESTING: usize = 1_000;
+
+//.. Limits on the resources a program may use, so programs which don't
+//   terminate (or take too much) can be stopped. `None` means unlimited.
+#[derive(Clone)]
+pub struct Limits {
+    //.. Number of expressions evaluated.
+    pub max_steps: Option<u64>,
+    //.. Number of nested function calls.
+    pub max_depth: Option<usize>,
+    //.. Size of a single value: the number of items in a list or map (or
+    //   fields of a record or variant) plus the sizes of the values in it, or
+    //   the number of bytes in a string. Nested values count towards the size
+    //   of the value containing them, so growing a value by nesting it in
+    //   itself is limited as well.
+    pub max_value_size: Option<usize>,
+    //.. Time since the program was started, or in the REPL, since the
+    //   evaluation of the current input was started.
+    pub time_limit: Option<Duration>,
+}
+
+impl Limits {
+    //.. The number of expressions that can be evaluated inside each other,
+    //   which follows from the call depth limit.
+    pub fn max_nesting(&self) -> Option<usize> {
+        self.max_depth.map(|max_depth| max_depth.saturating_mul(NESTING_PER_CALL).saturating_add(MIN_NESTING))
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_steps: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_value_size: None,
+            time_limit: None,
+        }
+    }
+}
+
+//.. Keeps track of the resources used during evaluation, and raises a
+//   `limit` error once one of the limits is exceeded.
+pub struct Budget {
+    limits: Limits,
+    steps: u64,
+    depth: usize,
+    nesting: usize,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            deadline: limits.time_limit.map(|time_limit| Instant::now() + time_limit),
+            limits,
+            steps: 0,
+            depth: 0,
+            nesting: 0,
+        }
+    }
+
+    //.. Starts counting from zero again, with a new deadline. Used for each
+    //   input of the REPL, so the limits apply to every input on its own.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.limits.clone());
+    }
+
+    //.. Counts the evaluation of an expression.
+    pub fn take_step(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.steps += 1;
+
+        if let Some(max_steps) = self.limits.max_steps {
+            if self.steps > max_steps {
+                return Err(Self::limit_error(format!(
+                    "step limit exceeded: the program took more than {} evaluation steps",
+                    max_steps
+                )));
+            }
+        }
+
+        if let (Some(deadline), Some(time_limit)) = (self.deadline, self.limits.time_limit) {
+            if Instant::now() > deadline {
+                return Err(Self::limit_error(format!(
+                    "time limit exceeded: the program ran for more than {} ms",
+                    time_limit.as_millis()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    //.. Counts entering the body of a function, which has to be followed by
+    //   `exit_call` once it's left (if entering succeeded).
+    pub fn enter_call(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(max_depth) = self.limits.max_depth {
+            if self.depth >= max_depth {
+                return Err(Self::limit_error(format!(
+                    "call depth limit exceeded: more than {} nested function calls",
+                    max_depth
+                )));
+            }
+        }
+
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    pub fn exit_call(&mut self) {
+        self.depth -= 1;
+    }
+
+    //.. Counts starting the evaluation of an expression inside the ones being
+    //   evaluated already, which has to be followed by `exit_evaluation` once
+    //   it's done (if entering succeeded).
+    pub fn enter_evaluation(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(max_nesting) = self.limits.max_nesting() {
+            if self.nesting >= max_nesting {
+                return Err(Self::limit_error(format!(
+                    "nesting limit exceeded: more than {} expressions evaluated inside each other",
+                    max_nesting
+                )));
+            }
+        }
+
+        self.nesting += 1;
+
+        Ok(())
+    }
+
+    pub fn exit_evaluation(&mut self) {
+        self.nesting -= 1;
+    }
+
+    //.. Checks the size of a list, map or string before it's created, for
+    //   functions that could otherwise allocate more than the limit at once.
+    pub fn check_size(&self, size: usize) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(max_value_size) = self.limits.max_value_size {
+            if size > max_value_size {
+                return Err(Self::limit_error(format!(
+                    "value size limit exceeded: a value is larger than the maximum size of {}",
+                    max_value_size
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn check_value_size(&self, value: &Expression) -> Result<(), Box<dyn std::error::Error>> {
+        match self.limits.max_value_size {
+            Some(max_value_size) => self.check_size(Self::value_size(value, max_value_size)),
+            None => Ok(()),
+        }
+    }
+
+    //.. The size of a value as limited by `max_value_size`, where counting
+    //   stops once it's larger than `max_size`, so the size of a huge value
+    //   is only partly counted.
+    fn value_size(value: &Expression, max_size: usize) -> usize {
+        let (length, nested_values): (usize, Box<dyn Iterator<Item = &Expression>>) = match value {
+            Expression::StringValue(string) => return string.len(),
+            Expression::List(items) => (items.len(), Box::new(items.iter())),
+            Expression::Record(fields) => (fields.len(), Box::new(fields.iter().map(|(_, value)| value))),
+            Expression::Variant(_, values) => (values.len(), Box::new(values.iter())),
+            Expression::Map(entries) => {
+                let key_sizes = entries.keys().map(Self::key_size).fold(0, usize::saturating_add);
+                (entries.len().saturating_add(key_sizes), Box::new(entries.values()))
+            },
+            _ => return 0,
+        };
+
+        let mut size = length;
+
+        for nested_value in nested_values {
+            if size > max_size {
+                break;
+            }
+
+            size = size.saturating_add(Self::value_size(nested_value, max_size - size));
+        }
+
+        size
+    }
+
+    fn key_size(key: &MapKey) -> usize {
+        match key {
+            MapKey::String(string) => string.len(),
+            MapKey::List(items) | MapKey::Variant(_, items) => {
+                items.iter().map(Self::key_size).fold(items.len(), usize::saturating_add)
+            },
+            MapKey::Record(fields) => {
+                fields.iter().map(|(_, value)| Self::key_size(value)).fold(fields.len(), usize::saturating_add)
+            },
+            MapKey::Null | MapKey::Boolean(_) | MapKey::Integer(_) => 0,
+        }
+    }
+
+    fn limit_error(message: String) -> Box<dyn std::error::Error> {
+        RuntimeError::new(ErrorKind::Limit, message).into()
+    }
+}
@@ -2,22 +2,191 @@ mod lexer;
 mod parser;
 mod ast;
 mod builtin_functions;
+mod error;
+mod capabilities;
+mod module;
+mod random;
+mod limits;
+mod interrupt;
 
+use crate::capabilities::{Capabilities, Capability};
+use crate::error::{ErrorKind, Exit, RuntimeError};
+use crate::limits::Limits;
 use crate::parser::Parser;
 
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::env;
+use std::path::PathBuf;
 use std::io::stdout;
 use std::io::Write;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+//.. Stack space reserved for each expression evaluated inside another one
+//   (see `Limits::max_nesting`), plus some slack for everything else, as
+//   evaluation is recursive. Evaluating an expression takes about 10 kilobytes
+//   of stack in debug builds, and less than one in release ones.
+const STACK_SIZE_PER_NESTING: usize = if cfg!(debug_assertions) { 16 * 1024 } else { 2 * 1024 };
+const MIN_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+struct Options {
+    load_prelude: bool,
+    seed: Option<i64>,
+    capabilities: Capabilities,
+    limits: Limits,
+    file_path: Option<String>,
+    //.. Arguments given after the file, available to the program as `args`.
+    program_arguments: Vec<String>,
+}
+
+fn main() {
+    interrupt::install_handler();
+
+    let exit_code = match parse_options() {
+        Ok(options) => {
+            let stack_size = options.limits.max_nesting()
+                .map_or(MIN_STACK_SIZE, |max_nesting| {
+                    max_nesting.saturating_mul(STACK_SIZE_PER_NESTING).saturating_add(MIN_STACK_SIZE)
+                });
+
+            //.. The interpreter runs on its own thread, as the stack of the main
+            //   thread can't be made large enough for deep recursion.
+            let interpreter = thread::Builder::new()
+                .stack_size(stack_size)
+                .spawn(move || exit_code(run(options)));
+
+            match interpreter {
+                //.. A panic has already been reported by the panic handler.
+                Ok(interpreter) => interpreter.join().unwrap_or(101),
+                Err(error) => exit_code(Err(error.into())),
+            }
+        },
+        Err(error) => exit_code(Err(error)),
+    };
+
+    //.. `process::exit` doesn't flush stdout, so output written by `print`
+    //   could get lost otherwise.
+    let _ = stdout().flush();
+    process::exit(exit_code);
+}
+
+//.. Reports an uncaught error, and gives back the exit code of the process.
+//   Interrupted programs exit with 130, like other programs stopped by
+//   Ctrl-C, and report where they were interrupted.
+fn exit_code(result: Result<(), Box<dyn std::error::Error>>) -> i32 {
+    let error = match result {
+        Ok(()) => return 0,
+        Err(error) => error,
+    };
+
+    let error = match error.downcast::<Exit>() {
+        Ok(exit) => return exit.0,
+        Err(error) => error,
+    };
+
+    match error.downcast::<RuntimeError>() {
+        Ok(runtime_error) if runtime_error.kind == ErrorKind::Interrupted => {
+            eprintln!("Error: {:?}\n{}", runtime_error, runtime_error.stack_trace());
+            130
+        },
+        Ok(runtime_error) => {
+            eprintln!("Error: {:?}", runtime_error);
+            1
+        },
+        Err(error) => {
+            eprintln!("Error: {:?}", error);
+            1
+        },
+    }
+}
+
+fn parse_option_value<T: std::str::FromStr>(option: &str, value: Option<String>) -> Result<T, Box<dyn std::error::Error>> {
+    let value = value.ok_or_else(|| format!("`{}` needs a value", option))?;
+
+    value.parse().map_err(|_| format!("`{}` needs a number, got `{}`", option, value).into())
+}
+
+fn parse_options() -> Result<Options, Box<dyn std::error::Error>> {
+    let mut load_prelude = true;
+    let mut seed = None;
+    let mut file_path = None;
+    //.. Everything is allowed unless `--sandbox` or an `--allow-...` flag is
+    //   given, in which case only what's allowed explicitly is.
+    let mut capabilities = None;
+    let mut limits = Limits::default();
+
+    let mut args = env::args().skip(1);
+
+    //.. Options come before the file to run, e.g.: "inflang --no-prelude main.inf",
+    //   the arguments after it are given to the program as `args`.
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--no-prelude" => load_prelude = false,
+            "--seed" => seed = Some(parse_option_value(&arg, args.next())?),
+            "--max-steps" => limits.max_steps = Some(parse_option_value(&arg, args.next())?),
+            "--max-depth" => limits.max_depth = Some(parse_option_value(&arg, args.next())?),
+            "--max-value-size" => limits.max_value_size = Some(parse_option_value(&arg, args.next())?),
+            "--time-limit" => {
+                let milliseconds = parse_option_value(&arg, args.next())?;
+                limits.time_limit = Some(Duration::from_millis(milliseconds));
+            },
+            "--sandbox" => {
+                capabilities.get_or_insert_with(Capabilities::none);
+            },
+            option if option.starts_with("--allow-") => {
+                let (name, directory) = match option["--allow-".len()..].split_once('=') {
+                    Some((name, directory)) => (name, Some(directory)),
+                    None => (&option["--allow-".len()..], None),
+                };
+
+                let capability = Capability::from_flag_name(name)
+                    .ok_or_else(|| format!("unknown option `{}`", option))?;
+
+                capabilities
+                    .get_or_insert_with(Capabilities::none)
+                    .grant(capability, directory)?;
+            },
+            option if option.starts_with("--") => {
+                return Err(format!("unknown option `{}`", option).into());
+            },
+            _ => {
+                file_path = Some(arg);
+                break;
+            },
+        }
+    }
+
+    Ok(Options {
+        load_prelude,
+        seed,
+        capabilities: capabilities.unwrap_or_else(Capabilities::all),
+        limits,
+        file_path,
+        program_arguments: args.collect(),
+    })
+}
+
+fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let Options { load_prelude, seed, capabilities, limits, file_path, program_arguments } = options;
+
+    let ctx = Rc::new(RefCell::new(ast::EvaluationContext::new(capabilities, limits)));
+    ctx.borrow_mut().set_program_arguments(program_arguments);
+
+    if let Some(seed) = seed {
+        ctx.borrow_mut().random.seed(seed);
+    }
+
+    if load_prelude {
+        ast::EvaluationContext::load_prelude(&ctx)?;
+    }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    let ctx = Rc::new(RefCell::new(ast::EvaluationContext::new()));
-    
     //.. Run file
-    if args.len() > 1 {
-        let mut parser = Parser::from_file(args[1].clone())?;
+    if let Some(file_path) = file_path {
+        ctx.borrow_mut().set_main_file(PathBuf::from(&file_path));
+
+        let mut parser = Parser::from_file(file_path)?;
 
         parser
             .parse_program()?
@@ -32,16 +201,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         stdout().flush()?;
 
         let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
+
+        //.. Stop at the end of the input (Ctrl-D)
+        if std::io::stdin().read_line(&mut input)? == 0 {
+            println!();
+            return Ok(());
+        }
+
+        interrupt::reset();
+        ctx.borrow_mut().budget.reset();
 
         let mut parser = Parser::new(input);
 
-        let evaluated_value = parser
-            .parse_expression()?
-            .evaluate(ctx.clone())?;
+        let result = parser
+            .parse_expression()
+            .and_then(|expression| expression.evaluate(ctx.clone()));
 
-        if evaluated_value != ast::Expression::Null {
-            println!("{}", evaluated_value);
+        //.. Errors (including interrupts) only end the evaluation of the
+        //   current input, so the bindings made so far are kept.
+        match result {
+            Ok(evaluated_value) if !evaluated_value.is_null() => println!("{}", evaluated_value),
+            Ok(_) => (),
+            Err(error) if error.is::<Exit>() => return Err(error),
+            Err(error) => eprintln!("Error: {:?}", error),
         }
     }
 }
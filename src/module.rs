@@ -0,0 +1,60 @@
+use crate::error::{ErrorKind, RuntimeError};
+use crate::lexer;
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+//.. Finds the file imported by `import "<path>"`. Relative paths are looked
+//   up relative to the directory of the importing file first (or the current
+//   directory, when not importing from a file), then relative to each
+//   directory in the `INFLANG_PATH` environment variable.
+pub fn resolve_path(path: &str, importing_file: Option<&Path>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let importing_directory = importing_file
+        .and_then(|file| file.parent())
+        .map(|directory| directory.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let mut search_directories = vec![importing_directory];
+
+    if let Some(inflang_path) = env::var_os("INFLANG_PATH") {
+        search_directories.extend(env::split_paths(&inflang_path));
+    }
+
+    //.. Joining an absolute path replaces the directory, so absolute paths
+    //   are only looked up once.
+    for directory in search_directories {
+        let candidate = directory.join(path);
+
+        if candidate.is_file() {
+            return Ok(candidate.canonicalize()?);
+        }
+    }
+
+    Err(RuntimeError::new(
+        ErrorKind::Import, format!("module `{}` not found", path)
+    ).into())
+}
+
+//.. The name a module is bound to when it's imported without `as`, which is
+//   its file name without extension, e.g. `list_utils` for "lib/list_utils.inf".
+pub fn namespace_name(module_path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let file_stem = module_path
+        .file_stem()
+        .map(|file_stem| file_stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let is_identifier = matches!(
+        lexer::Lexer::new(file_stem.clone()).next_token(),
+        Ok(lexer::Token { token_type: lexer::TokenType::Identifier, value }) if value == file_stem
+    );
+
+    if !is_identifier {
+        return Err(RuntimeError::new(ErrorKind::Import, format!(
+            "`{}` can't be used as a name for module `{}`, use `import ... as <name>` instead",
+            file_stem,
+            module_path.display(),
+        )).into());
+    }
+
+    Ok(file_stem)
+}
@@ -25,7 +25,7 @@ impl Parser {
     }
 
     fn peek_token(&mut self) -> Result<lexer::Token, Box<dyn std::error::Error>> {
-        if self.lexed_tokens.len() == 0 {
+        if self.lexed_tokens.is_empty() {
             self.lexed_tokens.push(self.lexer.next_token()?);
         }
 
@@ -84,8 +84,7 @@ impl Parser {
         let mut expressions = Vec::new();
 
         while self.accept(lexer::TokenType::RCurlyBrace)?.is_none() {
-            expressions.push(self.parse_expression()?);
-            self.expect(lexer::TokenType::Semicolon)?;
+            expressions.push(self.parse_statement(lexer::TokenType::RCurlyBrace)?);
         }
 
         if expressions.is_empty() {
@@ -127,89 +126,395 @@ impl Parser {
         Ok(ast::Expression::While(Box::new(condition), Box::new(body)))
     }
 
-    fn parse_fn_declaration(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+    fn parse_type_declaration(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. type <name> = <constructors, separated by `|`>
+        //.. where a constructor is written as either:
+        //   <Name> or <Name>(<field names, separated by `,`>)
+
+        self.expect(lexer::TokenType::Type)?;
+
+        let type_name = self.expect(lexer::TokenType::Identifier)?.value;
+
+        self.expect(lexer::TokenType::AssignmentOperator)?;
+
+        let mut constructors: Vec<(String, Vec<String>)> = Vec::new();
+
+        loop {
+            let constructor_name = self.expect(lexer::TokenType::Identifier)?.value;
+
+            if !ast::Pattern::is_constructor_name(&constructor_name) {
+                return Err(format!(
+                    "constructor `{}` of type `{}` should start with an uppercase letter",
+                    constructor_name, type_name
+                ).into());
+            }
+
+            if constructors.iter().any(|(name, _)| *name == constructor_name) {
+                return Err(format!(
+                    "constructor `{}` is defined more than once in type `{}`",
+                    constructor_name, type_name
+                ).into());
+            }
+
+            let mut field_names = Vec::new();
+
+            if self.accept(lexer::TokenType::LParen)?.is_some() {
+                let mut was_separated = true;
+
+                while self.accept(lexer::TokenType::RParen)?.is_none() {
+                    if !was_separated {
+                        return Err("unseparated field name in type declaration".into());
+                    }
+
+                    field_names.push(self.expect(lexer::TokenType::Identifier)?.value);
+
+                    was_separated = self.accept(lexer::TokenType::Comma)?.is_some();
+                }
+            }
+
+            constructors.push((constructor_name, field_names));
+
+            if self.accept(lexer::TokenType::Pipe)?.is_none() {
+                break;
+            }
+        }
+
+        Ok(ast::Expression::TypeDeclaration(type_name, constructors))
+    }
+
+    fn parse_pattern(&mut self) -> Result<ast::Pattern, Box<dyn std::error::Error>> {
+        //.. _, <binding name>, <literal>, <Constructor> or
+        //   <Constructor>(<patterns, separated by `,`>)
+
+        let tok = self.peek_token()?;
+        self.consume_token();
+
+        match tok.token_type {
+            lexer::TokenType::Identifier if tok.value == "_" => Ok(ast::Pattern::Wildcard),
+            lexer::TokenType::Identifier if ast::Pattern::is_constructor_name(&tok.value) => {
+                let mut field_patterns = Vec::new();
+
+                if self.accept(lexer::TokenType::LParen)?.is_some() {
+                    let mut was_separated = true;
+
+                    while self.accept(lexer::TokenType::RParen)?.is_none() {
+                        if !was_separated {
+                            return Err("unseparated pattern in constructor pattern".into());
+                        }
+
+                        field_patterns.push(self.parse_pattern()?);
+
+                        was_separated = self.accept(lexer::TokenType::Comma)?.is_some();
+                    }
+                }
+
+                Ok(ast::Pattern::Constructor(tok.value, field_patterns))
+            },
+            lexer::TokenType::Identifier => Ok(ast::Pattern::Binding(tok.value)),
+            lexer::TokenType::Integer => {
+                Ok(ast::Pattern::Literal(ast::Expression::IntegerValue(tok.value.parse()?)))
+            },
+            lexer::TokenType::StringLiteral => {
+                Ok(ast::Pattern::Literal(ast::Expression::StringValue(tok.value)))
+            },
+            lexer::TokenType::True => Ok(ast::Pattern::Literal(ast::Expression::BooleanValue(true))),
+            lexer::TokenType::False => Ok(ast::Pattern::Literal(ast::Expression::BooleanValue(false))),
+            _ => Err(format!("unexpected `{}`; no valid pattern starts with this", tok).into()),
+        }
+    }
+
+    fn parse_match(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. match <expression> { <arms> }
+        //.. where an arm is written as: <pattern> => <block>, and arms may
+        //   optionally be separated by `,`
+
+        self.expect(lexer::TokenType::Match)?;
+
+        let matched_value = self.parse_expression()?;
+
+        self.expect(lexer::TokenType::LCurlyBrace)?;
+
+        let mut arms = Vec::new();
+
+        while self.accept(lexer::TokenType::RCurlyBrace)?.is_none() {
+            let pattern = self.parse_pattern()?;
+
+            self.expect(lexer::TokenType::FatArrow)?;
+
+            arms.push((pattern, self.parse_block()?));
+
+            self.accept(lexer::TokenType::Comma)?;
+        }
+
+        if arms.is_empty() {
+            return Err("expected at least one arm in match expression, got none".into());
+        }
+
+        Ok(ast::Expression::Match(Box::new(matched_value), arms))
+    }
+
+    fn parse_throw(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. throw <expression>
+
+        self.expect(lexer::TokenType::Throw)?;
+
+        let value = self.parse_expression()?;
+
+        Ok(ast::Expression::Throw(Box::new(value)))
+    }
+
+    fn parse_import(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. import <string literal>
+        //.. optionally followed by: as <identifier>
+
+        self.expect(lexer::TokenType::Import)?;
+
+        let path = self.expect(lexer::TokenType::StringLiteral)?.value;
+
+        let namespace_name = if self.accept(lexer::TokenType::As)?.is_some() {
+            Some(self.expect(lexer::TokenType::Identifier)?.value)
+        } else {
+            None
+        };
+
+        Ok(ast::Expression::Import(path, namespace_name))
+    }
+
+    fn parse_try_catch(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. try <block> catch <identifier> <block>
+        //.. optionally followed by: finally <block>
+
+        self.expect(lexer::TokenType::Try)?;
+
+        let try_block = self.parse_block()?;
+
+        self.expect(lexer::TokenType::Catch)?;
+
+        let error_name = self.expect(lexer::TokenType::Identifier)?.value;
+
+        let catch_block = self.parse_block()?;
+
+        let finally_block = if self.accept(lexer::TokenType::Finally)?.is_some() {
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+
+        Ok(ast::Expression::TryCatch(
+            Box::new(try_block),
+            error_name,
+            Box::new(catch_block),
+            Box::new(finally_block),
+        ))
+    }
+
+    fn parse_short_circuit(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. and(<operands, separated by `,`>) or or(<operands, separated by `,`>)
+        //.. These look like function calls, but aren't: operands are only
+        //   evaluated until the result is known.
+
+        let operator = self.peek_token()?.token_type;
+        self.consume_token();
+
+        self.expect(lexer::TokenType::LParen)?;
+
+        let mut operands = Vec::new();
+        let mut was_separated = true;
+
+        while self.accept(lexer::TokenType::RParen)?.is_none() {
+            if !was_separated {
+                return Err(format!("unseparated operand in `{}`", operator).into());
+            }
+
+            operands.push(self.parse_expression()?);
+
+            was_separated = self.accept(lexer::TokenType::Comma)?.is_some();
+        }
+
+        if operands.len() < 2 {
+            return Err(format!("expected at least two operands in `{}`", operator).into());
+        }
+
+        Ok(if operator == lexer::TokenType::And {
+            ast::Expression::And(operands)
+        } else {
+            ast::Expression::Or(operands)
+        })
+    }
+
+    fn parse_fn_declaration(&mut self) -> Result<(ast::Expression, bool), Box<dyn std::error::Error>> {
         //.. fn (<argument names, separated by `,`>) <block>
-        
+        //.. or, to bind the function to a name:
+        //   fn <name>(<argument names, separated by `,`>) <block>
+        //.. Also gives back whether the body is a block (and not a single
+        //   expression), as only then the declaration ends with a `}`.
+
         self.expect(lexer::TokenType::Fn)?;
 
+        let fn_name = self.accept(lexer::TokenType::Identifier)?.map(|token| token.value);
+
+        let (function, has_block_body) = self.parse_fn_arguments_and_body()?;
+
+        Ok(match fn_name {
+            Some(fn_name) => (ast::Expression::FnDeclaration(fn_name, Box::new(function)), has_block_body),
+            None => (function, has_block_body),
+        })
+    }
+
+    fn parse_fn_arguments_and_body(&mut self) -> Result<(ast::Expression, bool), Box<dyn std::error::Error>> {
+        //.. (<parameters, separated by `,`>) <body>
+
         self.expect(lexer::TokenType::LParen)?;
 
-        let mut argument_names = Vec::new();
+        let (parameters, rest_parameter) = self.parse_parameters(lexer::TokenType::RParen)?;
+
+        let has_block_body = self.peek_token()?.token_type == lexer::TokenType::LCurlyBrace;
+        let fn_body = self.parse_fn_body()?;
+
+        Ok((ast::Expression::Fn(parameters, rest_parameter, Box::new(fn_body), None), has_block_body))
+    }
+
+    fn parse_lambda(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. |<parameters, separated by `,`>| <body>
+        //.. This is a shorthand for fn (<parameters>) <body>, mostly useful for
+        //   passing small functions as an argument, e.g.: "map(|n| *(n, n), xs)"
+
+        self.expect(lexer::TokenType::Pipe)?;
+
+        let (parameters, rest_parameter) = self.parse_parameters(lexer::TokenType::Pipe)?;
+
+        let fn_body = self.parse_fn_body()?;
+
+        Ok(ast::Expression::Fn(parameters, rest_parameter, Box::new(fn_body), None))
+    }
+
+    fn parse_parameters(&mut self, closing_token_type: lexer::TokenType) -> Result<(Vec<ast::Parameter>, Option<String>), Box<dyn std::error::Error>> {
+        //.. <parameters, separated by `,`>, followed by the closing token
+        //.. where a parameter is written as <name>, or as <name> = <default>
+        //   to make it optional. The last parameter may be written as ..<name>
+        //   to collect any remaining arguments in a list.
+
+        let mut parameters: Vec<ast::Parameter> = Vec::new();
+        let mut rest_parameter = None;
         let mut was_separated = true;
 
-        while self.accept(lexer::TokenType::RParen)?.is_none() {
+        while self.accept(closing_token_type.clone())?.is_none() {
             if !was_separated {
                 return Err("unseparated argument name in fn declaration".into());
             }
 
-            let argument_name = self.expect(lexer::TokenType::Identifier)?;
-            argument_names.push(argument_name.value);
-            
+            if rest_parameter.is_some() {
+                return Err("rest parameter should be the last parameter in fn declaration".into());
+            }
+
+            if self.accept(lexer::TokenType::DotDot)?.is_some() {
+                rest_parameter = Some(self.expect(lexer::TokenType::Identifier)?.value);
+            } else {
+                let name = self.expect(lexer::TokenType::Identifier)?.value;
+
+                if parameters.iter().any(|parameter| parameter.name == name) {
+                    return Err(format!("parameter `{}` is declared more than once", name).into());
+                }
+
+                let default_value = if self.accept(lexer::TokenType::AssignmentOperator)?.is_some() {
+                    Some(self.parse_expression()?)
+                } else {
+                    None
+                };
+
+                if default_value.is_none() && parameters.iter().any(|parameter| parameter.default_value.is_some()) {
+                    return Err(format!(
+                        "parameter `{}` without a default value follows one with a default value",
+                        name
+                    ).into());
+                }
+
+                parameters.push(ast::Parameter { name, default_value });
+            }
+
             was_separated = self.accept(lexer::TokenType::Comma)?.is_some();
         }
 
-        let fn_body = self.parse_block()?;
+        Ok((parameters, rest_parameter))
+    }
 
-        if argument_names.len() == 0 {
-            return Ok(ast::Expression::Fn(None, Box::new(fn_body)));
-        }
+    fn parse_fn_body(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. <block> or <expression>
+        //.. A body consisting of a single expression is turned into a block
+        //   containing only that expression. As a body starting with `{` is
+        //   always a block, returning a record literal requires a block.
 
-        //.. Functions are composed of nested unary functions. While looping
-        //   through the argument names, `last_fn` is the outer function every
-        //   time, which will then become the inner function.
-        let mut last_fn = ast::Expression::Fn(
-            Some(argument_names.pop().unwrap()),
-            Box::new(fn_body),
-        );
-        argument_names.reverse();
-
-        for argument_name in argument_names {
-            last_fn = ast::Expression::Fn(
-                Some(argument_name),
-                Box::new(last_fn),
-            );
+        if self.peek_token()?.token_type == lexer::TokenType::LCurlyBrace {
+            self.parse_block()
+        } else {
+            Ok(ast::Expression::Block(vec![self.parse_expression()?]))
         }
-
-        Ok(last_fn)
     }
 
     fn parse_fn_call(&mut self, expr: ast::Expression) -> Result<ast::Expression, Box<dyn std::error::Error>> {
         //.. <expr>(<arguments, separated by `,`>)
-        //.. This gets turned into a nested FnCall, e.g.: 
-        //   FnCall(FnCall(<expr>, <argument>), <argument>)
+        //.. where arguments may be named using <parameter name>: <expression>,
+        //   after all positional arguments
 
         self.expect(lexer::TokenType::LParen)?;
 
+        let mut arguments = Vec::new();
+        let mut named_arguments: Vec<(String, ast::Expression)> = Vec::new();
         let mut was_separated = true;
-        let mut fn_call = ast::Expression::Null;
 
         while self.accept(lexer::TokenType::RParen)?.is_none() {
             if !was_separated {
                 return Err("unseparated argument name in fn call".into());
             }
 
-            if fn_call.is_null() {
-                fn_call = ast::Expression::FnCall(
-                    Box::new(expr.clone()), Box::new(Some(self.parse_expression()?))
-                );
-            } else {
-                fn_call = ast::Expression::FnCall(
-                    Box::new(fn_call), Box::new(Some(self.parse_expression()?))
-                );
+            let argument = self.parse_expression()?;
+
+            match argument {
+                ast::Expression::Identifier(name) if self.accept(lexer::TokenType::Colon)?.is_some() => {
+                    named_arguments.push((name, self.parse_expression()?));
+                },
+                _ if !named_arguments.is_empty() => {
+                    return Err("positional argument given after named argument in fn call".into());
+                },
+                argument => arguments.push(argument),
             }
             
             was_separated = self.accept(lexer::TokenType::Comma)?.is_some();
         }
-        
-        //.. If there were no arguments between the parentheses, fn_call will still
-        //   be ast::Expression::Null.
-        if fn_call.is_null() {
-            fn_call = ast::Expression::FnCall(
-                Box::new(expr.clone()), Box::new(None),
-            );
+
+        Ok(ast::Expression::FnCall(Box::new(expr), arguments, named_arguments))
+    }
+
+    fn parse_index(&mut self, expr: ast::Expression) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. <expr>[<index>] or <expr>[<start>..<end>], where both the start
+        //   and the end of a slice may be left out
+
+        self.expect(lexer::TokenType::LSquareBracket)?;
+
+        let start = if self.peek_token()?.token_type == lexer::TokenType::DotDot {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+
+        if self.accept(lexer::TokenType::DotDot)?.is_none() {
+            self.expect(lexer::TokenType::RSquareBracket)?;
+
+            return match start {
+                Some(index) => Ok(ast::Expression::Index(Box::new(expr), Box::new(index))),
+                None => Err("expected index between `[` and `]`, got none".into()),
+            };
         }
 
-        Ok(fn_call)
+        let end = if self.peek_token()?.token_type == lexer::TokenType::RSquareBracket {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+
+        self.expect(lexer::TokenType::RSquareBracket)?;
+
+        Ok(ast::Expression::Slice(Box::new(expr), Box::new(start), Box::new(end)))
     }
 
     fn parse_list(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
@@ -233,19 +538,160 @@ impl Parser {
         Ok(ast::Expression::List(items))
     }
 
+    fn parse_map(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. #{ <key: value pairs, separated by `,`> }
+
+        self.expect(lexer::TokenType::HashLCurlyBrace)?;
+
+        let mut entries = Vec::new();
+        let mut was_separated = true;
+
+        while self.accept(lexer::TokenType::RCurlyBrace)?.is_none() {
+            if !was_separated {
+                return Err("unseparated entry in map".into());
+            }
+
+            let key = self.parse_expression()?;
+            self.expect(lexer::TokenType::Colon)?;
+            let value = self.parse_expression()?;
+
+            entries.push((key, value));
+
+            was_separated = self.accept(lexer::TokenType::Comma)?.is_some();
+        }
+
+        Ok(ast::Expression::MapLiteral(entries))
+    }
+
+    fn parse_record(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. { <field: value pairs, separated by `,`> }
+        //.. or, to copy a record with some of its fields replaced:
+        //   { ..<record>, <field: value pairs, separated by `,`> }
+
+        self.expect(lexer::TokenType::LCurlyBrace)?;
+
+        let base_record = if self.accept(lexer::TokenType::DotDot)?.is_some() {
+            let base_record = self.parse_expression()?;
+
+            if self.peek_token()?.token_type != lexer::TokenType::RCurlyBrace {
+                self.expect(lexer::TokenType::Comma)?;
+            }
+
+            Some(base_record)
+        } else {
+            None
+        };
+
+        let mut fields: Vec<(String, ast::Expression)> = Vec::new();
+        let mut was_separated = true;
+
+        while self.accept(lexer::TokenType::RCurlyBrace)?.is_none() {
+            if !was_separated {
+                return Err("unseparated field in record".into());
+            }
+
+            let field_name = self.expect(lexer::TokenType::Identifier)?.value;
+
+            if fields.iter().any(|(name, _)| *name == field_name) {
+                return Err(format!("field `{}` is given more than once in record", field_name).into());
+            }
+
+            self.expect(lexer::TokenType::Colon)?;
+            fields.push((field_name, self.parse_expression()?));
+
+            was_separated = self.accept(lexer::TokenType::Comma)?.is_some();
+        }
+
+        Ok(match base_record {
+            Some(base_record) => ast::Expression::RecordUpdate(Box::new(base_record), fields),
+            None => ast::Expression::Record(fields),
+        })
+    }
+
     pub fn parse_program(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
         let mut expressions = Vec::new();
 
         while self.accept(lexer::TokenType::EOF)?.is_none() {
-            expressions.push(self.parse_expression()?);
-            self.expect(lexer::TokenType::Semicolon)?;
+            expressions.push(self.parse_statement(lexer::TokenType::EOF)?);
         }
 
         Ok(ast::Expression::Program(expressions))
     }
+
+    fn parse_statement(&mut self, closing_token_type: lexer::TokenType) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. <expression>; or <block statement>
+        //.. Block statements are `if`, `while`, `match`, `try` and named `fn`
+        //   declarations with a block body at the start of a statement. They
+        //   end at their last `}`, so whatever follows it starts the next
+        //   statement, e.g. a list on the next line isn't taken as an index
+        //   into their value. This is why the `;` after them may be left out.
+        //   A `fn` with a single expression as its body needs a `;`, as that
+        //   expression would go on into the next line. As part of a larger
+        //   expression, like "let x = match y { ... }.name", they're followed
+        //   by calls, indexing and so on like any other expression. The `;`
+        //   after the last statement of a block or program may be left out
+        //   as well.
+
+        let (statement, is_block_statement) = match self.peek_token()?.token_type {
+            lexer::TokenType::If
+            | lexer::TokenType::While
+            | lexer::TokenType::Match
+            | lexer::TokenType::Try => (self.parse_primary_expression()?, true),
+
+            lexer::TokenType::Fn => match self.parse_fn_declaration()? {
+                (declaration @ ast::Expression::FnDeclaration(_, _), has_block_body) => (declaration, has_block_body),
+                (function, _) => {
+                    let expr = self.parse_postfix_operations(function)?;
+                    (self.parse_pipeline(expr)?, false)
+                },
+            },
+
+            _ => (self.parse_expression()?, false),
+        };
+
+        if self.accept(lexer::TokenType::Semicolon)?.is_none()
+            && !is_block_statement
+            && self.peek_token()?.token_type != closing_token_type
+        {
+            self.expect(lexer::TokenType::Semicolon)?;
+        }
+
+        Ok(statement)
+    }
  
     pub fn parse_expression(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
-        let mut expr = match self.peek_token()? {
+        let expr = self.parse_postfix_expression()?;
+
+        self.parse_pipeline(expr)
+    }
+
+    fn parse_pipeline(&mut self, mut expr: ast::Expression) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        //.. <expression> |> <function>
+        //.. The value on the left is passed as the last argument to the
+        //   function on the right, so "xs |> map(inc) |> print_line" is the
+        //   same as "print_line(map(inc, xs))".
+
+        while self.accept(lexer::TokenType::PipeOperator)?.is_some() {
+            expr = match self.parse_postfix_expression()? {
+                ast::Expression::FnCall(function, mut arguments, named_arguments) => {
+                    arguments.push(expr);
+                    ast::Expression::FnCall(function, arguments, named_arguments)
+                },
+                function => ast::Expression::FnCall(Box::new(function), vec![expr], Vec::new()),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_postfix_expression(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        let expr = self.parse_primary_expression()?;
+
+        self.parse_postfix_operations(expr)
+    }
+
+    fn parse_primary_expression(&mut self) -> Result<ast::Expression, Box<dyn std::error::Error>> {
+        match self.peek_token()? {
             lexer::Token {
                 token_type: lexer::TokenType::Let,
                 value: _,
@@ -259,7 +705,46 @@ impl Parser {
             lexer::Token {
                 token_type: lexer::TokenType::Fn,
                 value: _,
-            } => self.parse_fn_declaration(),
+            } => self.parse_fn_declaration().map(|(function, _)| function),
+
+            lexer::Token {
+                token_type: lexer::TokenType::Pipe,
+                value: _,
+            } => self.parse_lambda(),
+
+            lexer::Token {
+                token_type: lexer::TokenType::Type,
+                value: _,
+            } => self.parse_type_declaration(),
+
+            lexer::Token {
+                token_type: lexer::TokenType::Match,
+                value: _,
+            } => self.parse_match(),
+
+            lexer::Token {
+                token_type: lexer::TokenType::Throw,
+                value: _,
+            } => self.parse_throw(),
+
+            lexer::Token {
+                token_type: lexer::TokenType::And,
+                value: _,
+            }
+            | lexer::Token {
+                token_type: lexer::TokenType::Or,
+                value: _,
+            } => self.parse_short_circuit(),
+
+            lexer::Token {
+                token_type: lexer::TokenType::Try,
+                value: _,
+            } => self.parse_try_catch(),
+
+            lexer::Token {
+                token_type: lexer::TokenType::Import,
+                value: _,
+            } => self.parse_import(),
 
             lexer::Token {
                 token_type: lexer::TokenType::Identifier,
@@ -295,6 +780,16 @@ impl Parser {
                 value: _,
             } => self.parse_list(),
 
+            lexer::Token {
+                token_type: lexer::TokenType::HashLCurlyBrace,
+                value: _,
+            } => self.parse_map(),
+
+            lexer::Token {
+                token_type: lexer::TokenType::LCurlyBrace,
+                value: _,
+            } => self.parse_record(),
+
             lexer::Token {
                 token_type: lexer::TokenType::True,
                 value: _,
@@ -317,18 +812,36 @@ impl Parser {
             } => Ok(ast::Expression::EndOfProgram),
 
             misc_token => {
-                return Err(format!(
+                Err(format!(
                     "unexpected `{}`; no valid expression starts with this",
                     misc_token
-                ).into());
+                ).into())
             }
-        }?;
+        }
+    }
 
+    fn parse_postfix_operations(&mut self, mut expr: ast::Expression) -> Result<ast::Expression, Box<dyn std::error::Error>> {
         //.. A function call can return a function so an expression may contain
         //   multiple function calls after each other, e.g.:
         //   "fn (x, y) { +(x, y); }(2)(3)"
-        while self.peek_token()?.token_type == lexer::TokenType::LParen {
-            expr = self.parse_fn_call(expr)?;
+        //   Indexing, slicing, field access and `?` are parsed in the same
+        //   loop, so they can be mixed with calls, e.g.: "get_users()?[0].name"
+        loop {
+            expr = match self.peek_token()?.token_type {
+                lexer::TokenType::LParen => self.parse_fn_call(expr)?,
+                lexer::TokenType::LSquareBracket => self.parse_index(expr)?,
+                lexer::TokenType::Dot => {
+                    self.consume_token();
+                    let field_name = self.expect(lexer::TokenType::Identifier)?.value;
+
+                    ast::Expression::FieldAccess(Box::new(expr), field_name)
+                },
+                lexer::TokenType::QuestionMark => {
+                    self.consume_token();
+                    ast::Expression::Propagate(Box::new(expr))
+                },
+                _ => break,
+            };
         }
 
         Ok(expr)
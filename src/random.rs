@@ -0,0 +1,70 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+//.. Pseudo-random number generator (xorshift64*), used by the `random_*`
+//   built-in functions. Not suitable for anything security related.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn from_seed(seed: i64) -> Self {
+        let mut random = Self { state: 0 };
+        random.seed(seed);
+        random
+    }
+
+    //.. Seeded with the current time, for when no seed was given.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as i64)
+            .unwrap_or_default();
+
+        Self::from_seed(nanos)
+    }
+
+    //.. Scrambles the seed (using a step of splitmix64), so similar seeds
+    //   don't give similar sequences and the state is never zero, which
+    //   xorshift can't get out of.
+    pub fn seed(&mut self, seed: i64) {
+        let mut z = (seed as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        self.state = if z == 0 { 0x9E37_79B9_7F4A_7C15 } else { z };
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    //.. A number from 0 up to (but not including) `bound`, where a bound of 0
+    //   stands for 2^64. Numbers in the incomplete last "round" of `bound` are
+    //   rejected, so every number is equally likely.
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return self.next_u64();
+        }
+
+        let rejection_zone = u64::MAX - u64::MAX % bound;
+
+        loop {
+            let n = self.next_u64();
+
+            if n < rejection_zone {
+                return n % bound;
+            }
+        }
+    }
+
+    //.. An integer from `low` up to and including `high`.
+    pub fn next_in_range(&mut self, low: i64, high: i64) -> i64 {
+        let width = (high as u64).wrapping_sub(low as u64).wrapping_add(1);
+
+        (low as u64).wrapping_add(self.next_below(width)) as i64
+    }
+}
@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+//.. Runs `source` as a program with the interpreter, given `options` before
+//   the file of the program. It's run from a temporary directory named after
+//   the test, which `set_up` can add files to.
+fn run_in_directory(test_name: &str, source: &str, options: &[&str], set_up: impl FnOnce(&Path)) -> Output {
+    let directory = std::env::temp_dir().join(format!("inflang-test-{}-{}", std::process::id(), test_name));
+    fs::create_dir_all(&directory).unwrap();
+
+    set_up(&directory);
+    fs::write(directory.join("main.inf"), source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_inflang"))
+        .current_dir(&directory)
+        .args(options)
+        .arg("main.inf")
+        .output()
+        .unwrap();
+
+    fs::remove_dir_all(&directory).unwrap();
+    output
+}
+
+//.. Runs `source` next to the given modules (file names and sources).
+fn run_with_modules(test_name: &str, source: &str, modules: &[(&str, &str)]) -> Output {
+    run_in_directory(test_name, source, &[], |directory| {
+        for (file_name, module_source) in modules {
+            fs::write(directory.join(file_name), module_source).unwrap();
+        }
+    })
+}
+
+fn run(test_name: &str, source: &str) -> Output {
+    run_with_modules(test_name, source, &[])
+}
+
+//.. Runs `source` and gives back what it printed, failing if it didn't run
+//   successfully.
+fn run_ok(test_name: &str, source: &str) -> String {
+    let output = run(test_name, source);
+
+    assert!(
+        output.status.success(),
+        "program failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn returned_closures_keep_their_scope() {
+    let output = run_ok("returned_closures", r#"
+        let adder = fn (n) { fn (x) { +(x, n); }; };
+        print_line(adder(2)(3));
+
+        fn lambda_adder(n) { |x| +(x, n) }
+        print_line(lambda_adder(10)(5));
+
+        let add_one = adder(1);
+        let n = 100;
+        print_line(map(add_one, [1, 2, 3]));
+    "#);
+
+    assert_eq!(output, "5\n15\n[2, 3, 4]\n");
+}
+
+#[test]
+fn functions_dont_see_the_variables_of_their_caller() {
+    let output = run_ok("caller_variables", r#"
+        let i = 5;
+        each(|x| print_line(+(x, i)), [1, 2]);
+
+        fn get_limit() { limit }
+        fn call_with_limit(limit) { get_limit() }
+        let limit = "global";
+        print_line(call_with_limit("local"));
+    "#);
+
+    assert_eq!(output, "6\n7\nglobal\n");
+}
+
+#[test]
+fn closures_from_modules_keep_the_module_scope() {
+    let output = run_with_modules("module_closures", r#"
+        import "counter.inf";
+        let secret = "importer";
+        print_line(counter.make_adder(1)(2));
+        print_line(map(counter.make_adder(10), [1, 2]));
+    "#, &[("counter.inf", r#"
+        let secret = 100;
+        fn add_secret(x) { +(x, secret) }
+        fn make_adder(n) { |x| +(add_secret(x), n) }
+    "#)]);
+
+    assert!(output.status.success(), "program failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "103\n[111, 112]\n");
+}
+
+#[test]
+fn match_bindings_stay_inside_their_arm() {
+    let output = run_ok("match_bindings", r#"
+        type Shape = Circle(r) | Rect(w, h);
+        let r = "outer";
+        let area = match Circle(2) {
+            Circle(r) => { *(r, r) },
+            Rect(w, h) => { *(w, h) },
+        };
+        print_line(area);
+        print_line(r);
+
+        let described = match Rect(1, 2) {
+            Rect(w) => { "one field" },
+            _ => { "no match" },
+        };
+        print_line(described);
+
+        let total = 0;
+        let i = 0;
+        while <(i, 3) {
+            match Circle(i) {
+                Circle(v) => { let total = +(total, v) },
+                Rect(w, h) => { 0 },
+            };
+            let i = inc(i);
+        }
+        print_line(total);
+    "#);
+
+    assert_eq!(output, "4\nouter\nno match\n3\n");
+}
+
+#[test]
+fn caught_errors_are_only_bound_in_the_catch_block() {
+    let output = run_ok("catch_binding", r#"
+        let e = "outer";
+        fn fail() { +(1, "a") }
+        let status = "ok";
+        try { fail() } catch e { print_line(e.kind); print_line(e.function); let status = "failed"; }
+        print_line(e);
+        print_line(status);
+
+        let handler = try { throw 1 } catch e { |x| +(x, e) };
+        print_line(handler(2));
+    "#);
+
+    assert_eq!(output, "type\nfail\nouter\nfailed\n3\n");
+}
+
+#[test]
+fn block_statements_end_at_their_closing_brace() {
+    let output = run_ok("block_statements", r#"
+        type Pair = Pair(a, b);
+        print_line({a: 1}.a);
+        print_line(#{"k": 2}["k"]);
+        print_line(match Pair(3, 4) { Pair(a, b) => { [a, b] } }[0]);
+
+        if true { print_line("if") } else { print_line("else") }
+        [4, 5] |> print_line;
+        fn five() { 5 }
+        ["after fn"] |> print_line;
+        fn double(x) *(x, 2);
+        [1, 2] |> map(double) |> print_line
+    "#);
+
+    assert_eq!(output, "1\n2\n3\nif\n[4, 5]\n[\"after fn\"]\n[2, 4]\n");
+
+    let output = run("expression_body_without_semicolon", r#"
+        fn double(x) *(x, 2)
+        let y = 1;
+    "#);
+
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected token of type ;"));
+}
+
+#[test]
+fn runaway_recursion_reaches_the_default_depth_limit() {
+    let output = run("runaway_recursion", r#"
+        fn forever(n) { forever(inc(n)) }
+        forever(0);
+    "#);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("call depth limit exceeded"));
+}
+
+#[test]
+fn sorting_values_that_cant_be_compared_fails_without_panicking() {
+    let output = run_ok("sort_mixed", r#"
+        let mixed = ["y", "b", 65, 38, "l", 27, 36, 12, "i", "r", "z", "e", 93, 87, 71, 55, 81, "r",
+                     66, 70, "c", "m", "z", "a", "k", 41, "c", 72, 18, "o", 40, "p", 70, 15, 69];
+        try { list_sort(mixed) } catch e { print_line(e.kind) }
+        try { sort_by(|x| x, mixed) } catch e { print_line(e.kind) }
+    "#);
+
+    assert_eq!(output, "type\ntype\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn file_access_stays_inside_the_allowed_directories() {
+    let options = ["--allow-console", "--allow-fs-read", "--allow-fs-write=data", "--allow-fs-write=data2"];
+
+    let output = run_in_directory("fs_containment", r#"
+        fn attempt(path) { try { write_file(path, "x"); "written" } catch e { e.kind } }
+        print_line(attempt("data/file.txt"));
+        print_line(attempt("data2/file.txt"));
+        print_line(attempt("outside/file.txt"));
+        print_line(attempt("data/../outside/file.txt"));
+        print_line(attempt("data/missing/../../outside/file.txt"));
+        print_line(attempt("data/dangling"));
+        print_line(attempt("data/linked_directory/file.txt"));
+        print_line(list_dir("outside"));
+    "#, &options, |directory| {
+        let outside = directory.join("outside");
+
+        for name in ["data", "data2", "outside"] {
+            fs::create_dir(directory.join(name)).unwrap();
+        }
+
+        std::os::unix::fs::symlink(outside.join("pwned.txt"), directory.join("data/dangling")).unwrap();
+        std::os::unix::fs::symlink(&outside, directory.join("data/linked_directory")).unwrap();
+    });
+
+    assert!(output.status.success(), "program failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "written\nwritten\npermission\npermission\npermission\npermission\npermission\n[]\n"
+    );
+}
+
+#[test]
+fn the_value_size_limit_counts_nested_values() {
+    let output = run_in_directory("nested_growth", r#"
+        let x = [1];
+        let i = 0;
+        while <(i, 40) { let x = [x, x]; let i = inc(i); }
+        print_line("not reached");
+    "#, &["--max-value-size", "10", "--max-steps", "100000"], |_| ());
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("value size limit exceeded"));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn deeply_nested_evaluation_fits_on_the_stack() {
+    let output = run_ok("nested_calls", r#"
+        fn f(n) { try { match Some(n) { Some(v) => { if eq(v, 0) { 0 } else { +(1, f(-(v, 1))) } } } } catch e { throw e } }
+        print_line(f(9990));
+    "#);
+
+    assert_eq!(output, "9990\n");
+
+    let nested_expression = format!("print_line({}0{});", "inc(".repeat(2000), ")".repeat(2000));
+    let output = run_in_directory("nested_expression", &nested_expression, &["--max-depth", "10"], |_| ());
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("nesting limit exceeded"));
+}